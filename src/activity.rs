@@ -0,0 +1,233 @@
+use crate::daemon::Severity;
+use crate::{short, SignatureInfo};
+
+/// An authority idle this long is reported as dormant, and waking up from it is an alert
+pub const DORMANT_DAYS: u64 = 30;
+/// Transactions decoded per check; the rest wait for the next one
+const MAX_DECODED: usize = 25;
+/// Known counterparties remembered per authority
+const MAX_COUNTERPARTIES: usize = 500;
+
+/// What the guardian knows about an upgrade authority's wallet
+#[derive(Clone, Debug)]
+pub struct AuthorityActivity {
+    pub authority: String,
+    /// Newest signature decoded; the next check starts after it
+    pub last_signature: Option<String>,
+    /// Block time of the newest transaction, None if the wallet never transacted
+    pub last_active: Option<i64>,
+    /// Addresses it has sent SOL or tokens to
+    pub counterparties: Vec<String>,
+}
+
+impl AuthorityActivity {
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "authority": self.authority,
+            "last_signature": self.last_signature,
+            "last_active": self.last_active,
+            "counterparties": self.counterparties,
+        })
+    }
+
+    pub fn from_json(json: &serde_json::Value) -> Option<Self> {
+        Some(Self {
+            authority: json["authority"].as_str()?.to_string(),
+            last_signature: json["last_signature"].as_str().map(|s| s.to_string()),
+            last_active: json["last_active"].as_i64(),
+            counterparties: json["counterparties"].as_array().into_iter().flatten()
+                .filter_map(|c| c.as_str().map(|c| c.to_string()))
+                .collect(),
+        })
+    }
+
+    /// Seconds since the last transaction, None if there never was one
+    pub fn idle_secs(&self, now: i64) -> Option<i64> {
+        self.last_active.map(|t| now - t)
+    }
+}
+
+/// Something the authority did that deserves an alert
+pub struct Action {
+    pub severity: Severity,
+    pub kind: &'static str,
+    /// Distinguishes occurrences for the alert fingerprint
+    pub state: String,
+    pub message: String,
+}
+
+/// Check `authority` for new transactions since `prev`. The first look at a wallet only
+/// records where it stands; after that every transaction it signed is decoded into actions.
+pub async fn poll(
+    client: &reqwest::Client,
+    prev: Option<&AuthorityActivity>,
+    authority: &str,
+) -> Result<(AuthorityActivity, Vec<Action>), Box<dyn std::error::Error>> {
+    let prev = match prev.filter(|p| p.authority == authority) {
+        Some(prev) => prev,
+        None => {
+            let newest = crate::get_recent_signatures(client, authority, 1).await?;
+            let activity = AuthorityActivity {
+                authority: authority.to_string(),
+                last_signature: newest.first().map(|s| s.signature.clone()),
+                last_active: newest.first().and_then(|s| s.block_time),
+                counterparties: Vec::new(),
+            };
+            return Ok((activity, Vec::new()));
+        }
+    };
+
+    // Everything since the last decoded signature, however many arrived
+    let mut sigs = crate::get_signature_history(client, authority, usize::MAX, prev.last_signature.as_deref()).await?;
+    let mut activity = prev.clone();
+    let mut actions = Vec::new();
+    if sigs.is_empty() {
+        return Ok((activity, actions));
+    }
+    let pending = sigs.len();
+    sigs.reverse();
+    // Oldest first, and only as far as we decode: an upgrade can't hide behind a burst of filler
+    sigs.truncate(MAX_DECODED);
+    let last = &sigs[sigs.len() - 1];
+    activity.last_signature = Some(last.signature.clone());
+    activity.last_active = last.block_time.or(prev.last_active);
+
+    let first = &sigs[0];
+    let dormant_secs = DORMANT_DAYS as i64 * 86_400;
+    match (prev.last_active, first.block_time) {
+        (None, _) => actions.push(Action {
+            severity: Severity::Critical,
+            kind: "authority_woke",
+            state: first.signature.clone(),
+            message: format!("FIRST-EVER ACTIVITY from authority {} (tx {})", authority, short(&first.signature)),
+        }),
+        (Some(before), Some(at)) if at - before >= dormant_secs => actions.push(Action {
+            severity: Severity::Critical,
+            kind: "authority_woke",
+            state: first.signature.clone(),
+            message: format!(
+                "DORMANT AUTHORITY ACTIVE after {} days: {} (tx {})",
+                (at - before) / 86_400, authority, short(&first.signature)
+            ),
+        }),
+        _ => {}
+    }
+
+    for sig in &sigs {
+        match crate::get_transaction(client, &sig.signature).await? {
+            Some(tx) => actions.extend(decode(&tx, sig, authority, &mut activity.counterparties)),
+            None => continue,
+        }
+    }
+    if pending > MAX_DECODED {
+        actions.push(Action {
+            severity: Severity::Medium,
+            kind: "authority_burst",
+            state: activity.last_signature.clone().unwrap_or_default(),
+            message: format!(
+                "Authority {} has {} new transactions; decoded the first {}, the rest follow in the next checks",
+                authority, pending, MAX_DECODED
+            ),
+        });
+    }
+    let excess = activity.counterparties.len().saturating_sub(MAX_COUNTERPARTIES);
+    activity.counterparties.drain(..excess);
+    Ok((activity, actions))
+}
+
+/// Outgoing actions in one transaction. Transactions the authority didn't sign are incoming
+/// and produce nothing.
+fn decode(tx: &serde_json::Value, sig: &SignatureInfo, authority: &str, counterparties: &mut Vec<String>) -> Vec<Action> {
+    let signed = tx["transaction"]["message"]["accountKeys"].as_array().into_iter().flatten()
+        .any(|k| k["pubkey"].as_str() == Some(authority) && k["signer"].as_bool() == Some(true));
+    if !signed {
+        return Vec::new();
+    }
+
+    let tx_ref = format!("tx {}{}", short(&sig.signature), if sig.err { ", failed" } else { "" });
+    let mut actions = Vec::new();
+    let mut buffer_write = false;
+    for ix in crate::parsed_instructions(tx) {
+        let kind = ix["parsed"]["type"].as_str().unwrap_or("");
+        let info = &ix["parsed"]["info"];
+        let text = |key: &str| info[key].as_str().unwrap_or("?").to_string();
+        match (ix["program"].as_str().unwrap_or(""), kind) {
+            ("bpf-upgradeable-loader", "upgrade") => actions.push(Action {
+                severity: Severity::Critical,
+                kind: "authority_upgrade",
+                state: sig.signature.clone(),
+                message: format!("Authority UPGRADED program {} ({})", text("programAccount"), tx_ref),
+            }),
+            ("bpf-upgradeable-loader", "setAuthority" | "setAuthorityChecked") => actions.push(Action {
+                severity: Severity::Critical,
+                kind: "authority_set_authority",
+                state: sig.signature.clone(),
+                message: format!(
+                    "Authority HANDED OVER {} to {} ({})",
+                    text("account"), info["newAuthority"].as_str().unwrap_or("none"), tx_ref
+                ),
+            }),
+            ("bpf-upgradeable-loader", "deployWithMaxDataLen") => actions.push(Action {
+                severity: Severity::High,
+                kind: "authority_deploy",
+                state: sig.signature.clone(),
+                message: format!("Authority deployed new program {} ({})", text("programAccount"), tx_ref),
+            }),
+            // Buffer writes come in hundreds per deploy; the buffer detector reports them once
+            ("bpf-upgradeable-loader", "write" | "initializeBuffer") => buffer_write = true,
+            ("bpf-upgradeable-loader", other) => actions.push(Action {
+                severity: Severity::Medium,
+                kind: "authority_loader",
+                state: sig.signature.clone(),
+                message: format!("Authority ran loader instruction {} ({})", other, tx_ref),
+            }),
+            ("system", "transfer" | "transferWithSeed") if info["source"].as_str() == Some(authority) => {
+                let amount = format!("{:.4} SOL", info["lamports"].as_u64().unwrap_or(0) as f64 / 1e9);
+                actions.push(transfer(&text("destination"), &amount, sig, &tx_ref, counterparties));
+            }
+            ("spl-token" | "spl-token-2022", "transfer" | "transferChecked")
+                if info["authority"].as_str() == Some(authority) =>
+            {
+                let amount = info["tokenAmount"]["uiAmountString"].as_str()
+                    .map(|a| a.to_string())
+                    .unwrap_or_else(|| text("amount"));
+                let amount = format!("{} of {}", amount, info["mint"].as_str().map(short).unwrap_or_else(|| "token".to_string()));
+                actions.push(transfer(&text("destination"), &amount, sig, &tx_ref, counterparties));
+            }
+            _ => {}
+        }
+    }
+
+    if actions.is_empty() && !buffer_write {
+        let programs: Vec<String> = tx["transaction"]["message"]["instructions"].as_array().into_iter().flatten()
+            .filter_map(|ix| ix["program"].as_str().or(ix["programId"].as_str()))
+            .map(|p| if p.len() > 20 { short(p) } else { p.to_string() })
+            .collect();
+        actions.push(Action {
+            severity: Severity::Medium,
+            kind: "authority_tx",
+            state: sig.signature.clone(),
+            message: format!("Authority signed a transaction calling {} ({})", programs.join(", "), tx_ref),
+        });
+    }
+    actions
+}
+
+fn transfer(destination: &str, amount: &str, sig: &SignatureInfo, tx_ref: &str, counterparties: &mut Vec<String>) -> Action {
+    let state = format!("{}:{}", sig.signature, destination);
+    if counterparties.iter().any(|c| c == destination) {
+        return Action {
+            severity: Severity::Medium,
+            kind: "authority_transfer",
+            state,
+            message: format!("Authority sent {} to {} ({})", amount, destination, tx_ref),
+        };
+    }
+    counterparties.push(destination.to_string());
+    Action {
+        severity: Severity::High,
+        kind: "authority_transfer_new",
+        state,
+        message: format!("Authority sent {} to NEW counterparty {} ({})", amount, destination, tx_ref),
+    }
+}
@@ -0,0 +1,300 @@
+use crate::daemon::{Alert, Severity};
+use crate::encoding::base64_encode;
+use std::future::Future;
+use std::pin::Pin;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+type SendResult<'a> = Pin<Box<dyn Future<Output = Result<(), String>> + Send + 'a>>;
+
+/// Longest a single sink may take; a hung webhook, relay or hook must not stall monitoring
+const DELIVERY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Somewhere an alert can be delivered
+pub trait AlertSink: Send + Sync {
+    fn name(&self) -> String;
+    fn send<'a>(&'a self, alert: &'a Alert) -> SendResult<'a>;
+}
+
+/// One line of human-readable text, shared by the chat and mail sinks
+fn summary(alert: &Alert) -> String {
+    format!("{} [{}] {}", alert.severity, alert.program, alert.message)
+}
+
+async fn post_json(url: &str, body: serde_json::Value) -> Result<(), String> {
+    let client = reqwest::Client::builder().timeout(DELIVERY_TIMEOUT).build().map_err(|e| e.to_string())?;
+    // Webhook URLs carry their secret in the path, so errors mustn't echo them
+    let resp = client.post(url).json(&body).send().await.map_err(|e| e.without_url().to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("HTTP {}", resp.status()));
+    }
+    Ok(())
+}
+
+/// POST the alert as JSON to any URL
+pub struct Webhook {
+    pub url: String,
+}
+
+impl AlertSink for Webhook {
+    fn name(&self) -> String {
+        // Only the host: the rest of the URL is often the secret
+        match reqwest::Url::parse(&self.url).ok().as_ref().and_then(|u| u.host_str()) {
+            Some(host) => format!("webhook {}", host),
+            None => "webhook".to_string(),
+        }
+    }
+
+    fn send<'a>(&'a self, alert: &'a Alert) -> SendResult<'a> {
+        Box::pin(post_json(&self.url, alert.to_json()))
+    }
+}
+
+/// Slack incoming webhook
+pub struct Slack {
+    pub url: String,
+}
+
+impl AlertSink for Slack {
+    fn name(&self) -> String {
+        "slack".to_string()
+    }
+
+    fn send<'a>(&'a self, alert: &'a Alert) -> SendResult<'a> {
+        Box::pin(post_json(&self.url, serde_json::json!({ "text": summary(alert) })))
+    }
+}
+
+/// Discord incoming webhook
+pub struct Discord {
+    pub url: String,
+}
+
+impl AlertSink for Discord {
+    fn name(&self) -> String {
+        "discord".to_string()
+    }
+
+    fn send<'a>(&'a self, alert: &'a Alert) -> SendResult<'a> {
+        Box::pin(post_json(&self.url, serde_json::json!({ "content": summary(alert) })))
+    }
+}
+
+/// Plain SMTP, no TLS or auth: point it at a local relay (postfix, msmtpd, ...)
+pub struct Smtp {
+    pub host: String,
+    pub port: u16,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+impl Smtp {
+    async fn deliver(&self, alert: &Alert) -> Result<(), String> {
+        let stream = tokio::net::TcpStream::connect((self.host.as_str(), self.port)).await
+            .map_err(|e| format!("{}:{}: {}", self.host, self.port, e))?;
+        let (read, mut write) = stream.into_split();
+        let mut reader = BufReader::new(read);
+
+        expect_reply(&mut reader, 220).await?;
+        let mut commands = vec!["HELO solscan".to_string(), format!("MAIL FROM:<{}>", self.from)];
+        commands.extend(self.to.iter().map(|to| format!("RCPT TO:<{}>", to)));
+        for command in commands {
+            write.write_all(format!("{}\r\n", command).as_bytes()).await.map_err(|e| e.to_string())?;
+            expect_reply(&mut reader, 250).await?;
+        }
+
+        write.write_all(b"DATA\r\n").await.map_err(|e| e.to_string())?;
+        expect_reply(&mut reader, 354).await?;
+        let body = serde_json::to_string_pretty(&alert.to_json()).unwrap_or_default();
+        // Subject is one line, RFC 2047-encoded since severities carry emoji
+        let subject: String = summary(alert).lines().next().unwrap_or_default().to_string();
+        let message = format!(
+            "From: {}\nTo: {}\nSubject: =?UTF-8?B?{}?=\nContent-Type: text/plain; charset=utf-8\n\n{}\n\n{}\n",
+            self.from, self.to.join(", "), base64_encode(subject.as_bytes()), alert.message, body,
+        );
+        // CRLF line endings, and dot-stuffing: a line starting with '.' gets another one
+        let message = message.replace("\r\n", "\n").replace('\n', "\r\n").replace("\r\n.", "\r\n..");
+        write.write_all(format!("{}.\r\n", message).as_bytes()).await.map_err(|e| e.to_string())?;
+        expect_reply(&mut reader, 250).await?;
+
+        let _ = write.write_all(b"QUIT\r\n").await;
+        Ok(())
+    }
+}
+
+/// Read a (possibly multi-line) SMTP reply and check its code
+async fn expect_reply(reader: &mut BufReader<tokio::net::tcp::OwnedReadHalf>, code: u16) -> Result<(), String> {
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await.map_err(|e| e.to_string())? == 0 {
+            return Err("SMTP server closed the connection".to_string());
+        }
+        // "250-..." continues, "250 ..." ends the reply
+        if line.as_bytes().get(3) == Some(&b'-') {
+            continue;
+        }
+        return match line.get(..3).and_then(|c| c.parse::<u16>().ok()) {
+            Some(c) if c == code => Ok(()),
+            _ => Err(format!("SMTP: expected {}, got '{}'", code, line.trim_end())),
+        };
+    }
+}
+
+impl AlertSink for Smtp {
+    fn name(&self) -> String {
+        format!("smtp {}:{}", self.host, self.port)
+    }
+
+    fn send<'a>(&'a self, alert: &'a Alert) -> SendResult<'a> {
+        Box::pin(self.deliver(alert))
+    }
+}
+
+/// A syslog line on /dev/log (read by journald and rsyslog).
+/// Without a socket, the line goes to stderr with a `<priority>` prefix journald understands.
+pub struct Syslog {
+    pub socket: String,
+}
+
+impl Syslog {
+    fn priority(severity: &Severity) -> u8 {
+        match severity {
+            Severity::Critical => 2,
+            Severity::High => 3,
+            Severity::Medium => 4,
+            Severity::Info => 6,
+        }
+    }
+
+    fn deliver(&self, alert: &Alert) -> Result<(), String> {
+        let text = format!("[{}] {}", alert.program, alert.message);
+        // facility daemon (3)
+        let line = format!("<{}>solscan[{}]: {}", 3 * 8 + Self::priority(&alert.severity), std::process::id(), text);
+        #[cfg(unix)]
+        {
+            let socket = std::os::unix::net::UnixDatagram::unbound().map_err(|e| e.to_string())?;
+            if socket.send_to(line.as_bytes(), &self.socket).is_ok() {
+                return Ok(());
+            }
+        }
+        eprintln!("<{}>{}", Self::priority(&alert.severity), text);
+        Ok(())
+    }
+}
+
+impl AlertSink for Syslog {
+    fn name(&self) -> String {
+        "syslog".to_string()
+    }
+
+    fn send<'a>(&'a self, alert: &'a Alert) -> SendResult<'a> {
+        Box::pin(async move { self.deliver(alert) })
+    }
+}
+
+/// Run `sh -c <command>` with the alert JSON on stdin
+pub struct Exec {
+    pub command: String,
+}
+
+impl Exec {
+    async fn deliver(&self, alert: &Alert) -> Result<(), String> {
+        let mut child = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .env("SOLSCAN_SEVERITY", alert.severity.name())
+            .env("SOLSCAN_PROGRAM", &alert.program)
+            .stdin(std::process::Stdio::piped())
+            // dispatch drops this future on timeout; the hook goes with it
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| e.to_string())?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(alert.to_json().to_string().as_bytes()).await.map_err(|e| e.to_string())?;
+        }
+        let status = child.wait().await.map_err(|e| e.to_string())?;
+        if !status.success() {
+            return Err(format!("exited with {}", status));
+        }
+        Ok(())
+    }
+}
+
+impl AlertSink for Exec {
+    fn name(&self) -> String {
+        format!("exec {}", self.command)
+    }
+
+    fn send<'a>(&'a self, alert: &'a Alert) -> SendResult<'a> {
+        Box::pin(self.deliver(alert))
+    }
+}
+
+/// A sink plus the least severe alert it should receive
+pub struct Route {
+    pub sink: Box<dyn AlertSink>,
+    pub min_severity: Severity,
+}
+
+/// Fans alerts out to the configured sinks
+#[derive(Default)]
+pub struct AlertRouter {
+    pub routes: Vec<Route>,
+}
+
+impl AlertRouter {
+    /// Load an alert config:
+    /// `{"sinks": [{"type": "slack", "url": "...", "min_severity": "high"}, {"type": "exec", "command": "..."}]}`
+    /// Types: webhook, slack, discord (url); smtp (host, port, from, to); syslog (socket); exec (command).
+    /// `min_severity` defaults to medium.
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let config: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| format!("{}: {}", path, e))?;
+        let mut routes = Vec::new();
+        for (i, s) in config["sinks"].as_array().into_iter().flatten().enumerate() {
+            let field = |key: &str| s[key].as_str().map(|v| v.to_string())
+                .ok_or_else(|| format!("{}: sink #{} needs \"{}\"", path, i + 1, key));
+            let sink: Box<dyn AlertSink> = match s["type"].as_str().unwrap_or("") {
+                "webhook" => Box::new(Webhook { url: field("url")? }),
+                "slack" => Box::new(Slack { url: field("url")? }),
+                "discord" => Box::new(Discord { url: field("url")? }),
+                "smtp" => Box::new(Smtp {
+                    host: s["host"].as_str().unwrap_or("localhost").to_string(),
+                    port: s["port"].as_u64().unwrap_or(25) as u16,
+                    from: field("from")?,
+                    to: match &s["to"] {
+                        serde_json::Value::String(to) => vec![to.clone()],
+                        to => to.as_array().into_iter().flatten()
+                            .filter_map(|t| t.as_str().map(|t| t.to_string()))
+                            .collect(),
+                    },
+                }),
+                "syslog" => Box::new(Syslog { socket: s["socket"].as_str().unwrap_or("/dev/log").to_string() }),
+                "exec" => Box::new(Exec { command: field("command")? }),
+                other => return Err(format!("{}: sink #{} has unknown type '{}'", path, i + 1, other).into()),
+            };
+            let min_severity = match s["min_severity"].as_str() {
+                Some(name) => name.parse().map_err(|e| format!("{}: sink #{}: {}", path, i + 1, e))?,
+                None => Severity::Medium,
+            };
+            routes.push(Route { sink, min_severity });
+        }
+        Ok(Self { routes })
+    }
+
+    /// Deliver to every matching sink. Failures and timeouts are reported, never fatal.
+    pub async fn dispatch(&self, alerts: &[Alert]) {
+        for alert in alerts {
+            for route in &self.routes {
+                if alert.severity.rank() < route.min_severity.rank() {
+                    continue;
+                }
+                match tokio::time::timeout(DELIVERY_TIMEOUT, route.sink.send(alert)).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => eprintln!("⚠️  Alert delivery via {} failed: {}", route.sink.name(), e),
+                    Err(_) => eprintln!("⚠️  Alert delivery via {} timed out after {}s", route.sink.name(), DELIVERY_TIMEOUT.as_secs()),
+                }
+            }
+        }
+    }
+}
+
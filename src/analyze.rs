@@ -35,6 +35,7 @@ impl WalletGraph {
     }
 
     /// Score wallets by similarity (Jaccard index on token sets)
+    #[allow(dead_code)]
     pub fn similarity(&self, w1: &str, w2: &str) -> f64 {
         let empty = HashSet::new();
         let s1 = self.holdings.get(w1).unwrap_or(&empty);
@@ -82,7 +83,7 @@ impl WalletGraph {
     }
 }
 
-pub fn print_analysis(graph: &WalletGraph) {
+pub fn print_analysis(graph: &WalletGraph, domains: &HashMap<String, String>) {
     println!("\n🔗 Multi-Wallet Analysis");
     println!("═══════════════════════════════════════════");
     println!("  Wallets tracked: {}", graph.balances.len());
@@ -114,8 +115,9 @@ pub fn print_analysis(graph: &WalletGraph) {
     if !whales.is_empty() {
         println!("\n  🐋 Top Whales:");
         for (addr, bal) in &whales {
-            println!("    {}...{}: {:.4} SOL",
-                &addr[..8], &addr[addr.len()-4..], bal);
+            println!("    {}...{}: {:.4} SOL{}",
+                &addr[..8], &addr[addr.len()-4..], bal,
+                domains.get(addr).map(|d| format!(" ({})", d)).unwrap_or_default());
         }
     }
 }
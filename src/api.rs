@@ -0,0 +1,162 @@
+use crate::daemon::{Alert, ProgramState};
+use crate::watchlist::WatchedProgram;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Recent alerts kept in memory for `/alerts`
+pub const RECENT_ALERTS: usize = 500;
+
+/// What the guardian knows after its latest cycle, shared with the HTTP server
+#[derive(Default)]
+pub struct StatusBoard {
+    pub started_at: i64,
+    pub cycles: u64,
+    pub last_cycle_at: i64,
+    pub last_cycle_secs: f64,
+    pub rpc_errors: u64,
+    /// severity name -> count since start
+    pub alerts_by_severity: BTreeMap<&'static str, u64>,
+    pub programs: Vec<WatchedProgram>,
+    pub states: HashMap<String, ProgramState>,
+    pub risk_scores: HashMap<String, u8>,
+    /// (recorded_at, alert), oldest first
+    pub recent: VecDeque<(i64, Alert)>,
+}
+
+pub type SharedStatus = Arc<Mutex<StatusBoard>>;
+
+impl StatusBoard {
+    pub fn record_alerts(&mut self, alerts: &[Alert], recorded_at: i64) {
+        for alert in alerts {
+            *self.alerts_by_severity.entry(alert.severity.name()).or_default() += 1;
+            self.recent.push_back((recorded_at, alert.clone()));
+        }
+        while self.recent.len() > RECENT_ALERTS {
+            self.recent.pop_front();
+        }
+    }
+
+    fn metrics(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: Vec<(String, f64)>| {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            for (labels, value) in samples {
+                let _ = writeln!(out, "{}{} {}", name, labels, value);
+            }
+        };
+
+        metric("solscan_guardian_cycles_total", "counter", "Completed monitoring cycles",
+            vec![(String::new(), self.cycles as f64)]);
+        metric("solscan_guardian_cycle_duration_seconds", "gauge", "Duration of the last cycle",
+            vec![(String::new(), self.last_cycle_secs)]);
+        metric("solscan_guardian_last_cycle_timestamp_seconds", "gauge", "Unix time the last cycle finished",
+            vec![(String::new(), self.last_cycle_at as f64)]);
+        metric("solscan_guardian_rpc_errors_total", "counter", "Failed RPC lookups",
+            vec![(String::new(), self.rpc_errors as f64)]);
+        metric("solscan_guardian_alerts_total", "counter", "Alerts raised since start, by severity",
+            ["critical", "high", "medium", "info"].iter()
+                .map(|s| (format!("{{severity=\"{}\"}}", s), *self.alerts_by_severity.get(s).unwrap_or(&0) as f64))
+                .collect());
+
+        let per_program = |value: &dyn Fn(&WatchedProgram) -> Option<f64>| -> Vec<(String, f64)> {
+            self.programs.iter()
+                .filter_map(|p| Some((program_labels(p), value(p)?)))
+                .collect()
+        };
+        metric("solscan_guardian_program_risk_score", "gauge", "Audit risk score (0-100)",
+            per_program(&|p| self.risk_scores.get(&p.id).map(|r| *r as f64)));
+        metric("solscan_guardian_program_upgradeable", "gauge", "1 if the program can be upgraded",
+            per_program(&|p| self.states.get(&p.id).map(|s| s.is_upgradeable as u8 as f64)));
+        metric("solscan_guardian_authority_balance_sol", "gauge", "SOL held by the upgrade authority",
+            per_program(&|p| self.states.get(&p.id).and_then(|s| s.authority_balance)));
+        metric("solscan_guardian_program_deploy_slot", "gauge", "Slot of the last deploy",
+            per_program(&|p| self.states.get(&p.id).and_then(|s| s.deploy_slot).map(|s| s as f64)));
+        out
+    }
+
+    fn status_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "started_at": self.started_at,
+            "cycles": self.cycles,
+            "last_cycle_at": self.last_cycle_at,
+            "last_cycle_secs": self.last_cycle_secs,
+            "rpc_errors": self.rpc_errors,
+            "programs": self.programs.iter().map(|p| serde_json::json!({
+                "id": p.id,
+                "name": p.name,
+                "category": p.category,
+                "risk_score": self.risk_scores.get(&p.id),
+                "state": self.states.get(&p.id).map(|s| s.to_json()),
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    fn alerts_json(&self, limit: usize) -> serde_json::Value {
+        serde_json::Value::Array(self.recent.iter().rev().take(limit).map(|(at, alert)| {
+            let mut json = alert.to_json();
+            json["recorded_at"] = serde_json::json!(at);
+            json
+        }).collect())
+    }
+}
+
+fn program_labels(p: &WatchedProgram) -> String {
+    let escape = |v: &str| v.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+    format!("{{program_id=\"{}\",program=\"{}\"}}", escape(&p.id), escape(&p.name))
+}
+
+/// Serve `/metrics`, `/status` and `/alerts[?limit=N]` until the process exits
+pub async fn serve(addr: &str, status: SharedStatus) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = tokio::net::TcpListener::bind(addr).await
+        .map_err(|e| format!("cannot listen on {}: {}", addr, e))?;
+    loop {
+        let (mut socket, _) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(_) => continue,
+        };
+        let status = status.clone();
+        tokio::spawn(async move {
+            // Requests are a single GET line plus headers; 8 KiB is plenty
+            let mut buf = vec![0u8; 8192];
+            let n = match tokio::time::timeout(std::time::Duration::from_secs(5), socket.read(&mut buf)).await {
+                Ok(Ok(n)) => n,
+                _ => return,
+            };
+            let request = String::from_utf8_lossy(&buf[..n]);
+            let (code, content_type, body) = respond(&request, &status);
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                code, content_type, body.len(), body
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        });
+    }
+}
+
+fn respond(request: &str, status: &SharedStatus) -> (&'static str, &'static str, String) {
+    let mut parts = request.lines().next().unwrap_or("").split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    if method != "GET" {
+        return ("405 Method Not Allowed", "text/plain", "only GET is supported\n".to_string());
+    }
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let board = match status.lock() {
+        Ok(board) => board,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    match path {
+        "/metrics" => ("200 OK", "text/plain; version=0.0.4", board.metrics()),
+        "/status" => ("200 OK", "application/json", board.status_json().to_string()),
+        "/alerts" => {
+            let limit = query.split('&')
+                .find_map(|kv| kv.strip_prefix("limit="))
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(100);
+            ("200 OK", "application/json", board.alerts_json(limit).to_string())
+        }
+        _ => ("404 Not Found", "text/plain", "try /metrics, /status or /alerts\n".to_string()),
+    }
+}
@@ -0,0 +1,274 @@
+use crate::{short, ParsedTokenAccount};
+
+const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+/// Recent transactions searched for token-account owner reassignments
+const OWNER_CHANGE_SCAN: usize = 25;
+/// What the owner-change scan can't see: a SetAuthority names the new owner only in its
+/// instruction data, so an account handed to the wallet isn't in the wallet's history
+const OWNER_CHANGE_SCOPE: &str = "accounts this wallet gave away in its recent transactions; accounts handed to it by another owner don't show up";
+
+/// Token approval exposure — delegations, third-party close authorities, owner changes
+pub struct ApprovalReport {
+    pub wallet: String,
+    pub delegations: Vec<Delegation>,
+    pub close_authorities: Vec<ThirdPartyCloseAuthority>,
+    /// Err when the history scan couldn't be completed
+    pub owner_changes: Result<Vec<OwnerChange>, String>,
+}
+
+pub struct Delegation {
+    pub token_account: String,
+    pub mint: String,
+    pub delegate: String,
+    pub delegate_kind: AddressKind,
+    pub allowance: String,
+    pub balance: String,
+    /// Allowance is u64::MAX — the classic drainer approval
+    pub unlimited: bool,
+}
+
+pub struct ThirdPartyCloseAuthority {
+    pub token_account: String,
+    pub mint: String,
+    pub close_authority: String,
+    pub authority_kind: AddressKind,
+}
+
+pub struct OwnerChange {
+    pub signature: String,
+    pub slot: u64,
+    pub token_account: String,
+    pub previous_owner: String,
+    pub new_owner: String,
+}
+
+/// What sits behind an address the wallet has granted rights to
+#[derive(Clone, Debug)]
+pub enum AddressKind {
+    KnownProgram(&'static str),
+    Program,
+    /// Data account owned by a program (typically a PDA)
+    ProgramAccount(String),
+    /// System-owned account — a plain keypair wallet
+    Wallet,
+    /// No account on chain — a keypair that holds no SOL, or an unfunded PDA
+    Unfunded,
+    /// The RPC lookup failed
+    Unknown,
+}
+
+impl AddressKind {
+    pub fn is_eoa(&self) -> bool {
+        matches!(self, AddressKind::Wallet | AddressKind::Unfunded)
+    }
+}
+
+impl std::fmt::Display for AddressKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AddressKind::KnownProgram(name) => write!(f, "program ({})", name),
+            AddressKind::Program => write!(f, "unknown program"),
+            AddressKind::ProgramAccount(owner) => match crate::scanner::program_name(owner) {
+                Some(name) => write!(f, "account owned by {}", name),
+                None => write!(f, "account owned by {}...{}", &owner[..8], &owner[owner.len()-4..]),
+            },
+            AddressKind::Wallet => write!(f, "EOA wallet"),
+            AddressKind::Unfunded => write!(f, "EOA / unfunded"),
+            AddressKind::Unknown => write!(f, "could not classify"),
+        }
+    }
+}
+
+pub async fn approval_report(
+    client: &reqwest::Client,
+    wallet: &str,
+) -> Result<ApprovalReport, Box<dyn std::error::Error>> {
+    let accounts = crate::get_parsed_token_accounts(client, wallet).await?;
+
+    let mut delegations = Vec::new();
+    let mut close_authorities = Vec::new();
+    for account in &accounts {
+        if let Some(delegate) = account.info["delegate"].as_str() {
+            delegations.push(delegation(client, account, delegate).await);
+        }
+        if let Some(close_authority) = account.info["closeAuthority"].as_str() {
+            if close_authority != wallet {
+                close_authorities.push(ThirdPartyCloseAuthority {
+                    token_account: account.pubkey.clone(),
+                    mint: account.mint().to_string(),
+                    close_authority: close_authority.to_string(),
+                    authority_kind: classify_address(client, close_authority).await,
+                });
+            }
+        }
+    }
+
+    let owner_changes = owner_changes(client, wallet).await.map_err(|e| e.to_string());
+
+    Ok(ApprovalReport {
+        wallet: wallet.to_string(),
+        delegations,
+        close_authorities,
+        owner_changes,
+    })
+}
+
+async fn delegation(client: &reqwest::Client, account: &ParsedTokenAccount, delegate: &str) -> Delegation {
+    let delegated = &account.info["delegatedAmount"];
+    Delegation {
+        token_account: account.pubkey.clone(),
+        mint: account.mint().to_string(),
+        delegate: delegate.to_string(),
+        delegate_kind: classify_address(client, delegate).await,
+        allowance: delegated["uiAmountString"].as_str().unwrap_or("0").to_string(),
+        balance: account.ui_amount_string(),
+        unlimited: delegated["amount"].as_str() == Some(&u64::MAX.to_string()),
+    }
+}
+
+pub async fn classify_address(client: &reqwest::Client, address: &str) -> AddressKind {
+    if let Some(name) = crate::scanner::program_name(address) {
+        return AddressKind::KnownProgram(name);
+    }
+    let result = crate::rpc_call(
+        client,
+        "getAccountInfo",
+        serde_json::json!([address, { "encoding": "base64", "dataSlice": { "offset": 0, "length": 0 } }]),
+    )
+    .await;
+    let account = match result {
+        Ok(r) if !r["value"].is_null() => r["value"].clone(),
+        Ok(_) => return AddressKind::Unfunded,
+        Err(_) => return AddressKind::Unknown,
+    };
+    let owner = account["owner"].as_str().unwrap_or("");
+    if account["executable"].as_bool().unwrap_or(false) {
+        AddressKind::Program
+    } else if owner == SYSTEM_PROGRAM_ID {
+        AddressKind::Wallet
+    } else {
+        AddressKind::ProgramAccount(owner.to_string())
+    }
+}
+
+/// SetAuthority(AccountOwner) instructions touching the wallet in its recent history
+async fn owner_changes(
+    client: &reqwest::Client,
+    wallet: &str,
+) -> Result<Vec<OwnerChange>, Box<dyn std::error::Error>> {
+    let sigs = crate::get_recent_signatures(client, wallet, OWNER_CHANGE_SCAN).await?;
+    let mut changes = Vec::new();
+
+    for sig in sigs.iter().filter(|s| !s.err) {
+        let Some(tx) = crate::get_transaction(client, &sig.signature).await? else { continue };
+        for ix in crate::parsed_instructions(&tx) {
+            let parsed = &ix["parsed"];
+            if parsed["type"].as_str() != Some("setAuthority")
+                || parsed["info"]["authorityType"].as_str() != Some("accountOwner")
+            {
+                continue;
+            }
+            let info = &parsed["info"];
+            let previous_owner = info["authority"].as_str()
+                .or_else(|| info["multisigAuthority"].as_str())
+                .unwrap_or("unknown");
+            let new_owner = info["newAuthority"].as_str().unwrap_or("none");
+            if previous_owner == wallet || new_owner == wallet {
+                changes.push(OwnerChange {
+                    signature: sig.signature.clone(),
+                    slot: sig.slot,
+                    token_account: info["account"].as_str().unwrap_or("").to_string(),
+                    previous_owner: previous_owner.to_string(),
+                    new_owner: new_owner.to_string(),
+                });
+            }
+        }
+        // Rate limit courtesy
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    }
+
+    Ok(changes)
+}
+
+pub fn to_json(report: &ApprovalReport) -> serde_json::Value {
+    serde_json::json!({
+        "address": report.wallet,
+        "delegations": report.delegations.iter().map(|d| serde_json::json!({
+            "token_account": d.token_account,
+            "mint": d.mint,
+            "delegate": d.delegate,
+            "delegate_kind": d.delegate_kind.to_string(),
+            "delegate_is_eoa": (!matches!(d.delegate_kind, AddressKind::Unknown)).then(|| d.delegate_kind.is_eoa()),
+            "allowance": d.allowance,
+            "unlimited": d.unlimited,
+            "balance": d.balance,
+        })).collect::<Vec<_>>(),
+        "close_authorities": report.close_authorities.iter().map(|c| serde_json::json!({
+            "token_account": c.token_account,
+            "mint": c.mint,
+            "close_authority": c.close_authority,
+            "authority_kind": c.authority_kind.to_string(),
+        })).collect::<Vec<_>>(),
+        "owner_changes": report.owner_changes.as_ref().ok().map(|changes| changes.iter().map(|o| serde_json::json!({
+            "signature": o.signature,
+            "slot": o.slot,
+            "token_account": o.token_account,
+            "previous_owner": o.previous_owner,
+            "new_owner": o.new_owner,
+        })).collect::<Vec<_>>()),
+        "owner_changes_error": report.owner_changes.as_ref().err(),
+        "owner_changes_scanned": OWNER_CHANGE_SCAN,
+        "owner_changes_scope": OWNER_CHANGE_SCOPE,
+    })
+}
+
+pub fn print_report(report: &ApprovalReport) {
+    println!("\n🔐 Token Approval Exposure: {}", short(&report.wallet));
+    println!("═══════════════════════════════════════════");
+
+    println!("\n  🤝 Active Delegations:");
+    if report.delegations.is_empty() {
+        println!("    None ✅");
+    }
+    for d in &report.delegations {
+        let flag = if d.unlimited || d.delegate_kind.is_eoa() { "⚠️ " } else { "  " };
+        println!("  {} {} → {} ({})", flag, short(&d.mint), short(&d.delegate), d.delegate_kind);
+        println!("       allowance: {}{} of {} held | account {}",
+            d.allowance,
+            if d.unlimited { " (UNLIMITED)" } else { "" },
+            d.balance,
+            short(&d.token_account));
+    }
+
+    println!("\n  🗑️  Third-Party Close Authorities:");
+    if report.close_authorities.is_empty() {
+        println!("    None ✅");
+    }
+    for c in &report.close_authorities {
+        println!("    {} can be closed by {} ({})", short(&c.token_account), short(&c.close_authority), c.authority_kind);
+    }
+
+    println!("\n  🔁 Token Account Owner Changes (last {} txs):", OWNER_CHANGE_SCAN);
+    println!("    Covers {}", OWNER_CHANGE_SCOPE);
+    match &report.owner_changes {
+        Err(e) => println!("    ⚠️  Scan incomplete, could not read the history: {}", e),
+        Ok(changes) if changes.is_empty() => println!("    None ✅"),
+        Ok(changes) => for o in changes {
+            let arrow = if o.previous_owner == report.wallet { "⚠️  given away" } else { "received" };
+            println!("    {} {} : {} → {} | slot {} | {}",
+                arrow, short(&o.token_account), short(&o.previous_owner), short(&o.new_owner), o.slot, short(&o.signature));
+        },
+    }
+
+    let risky = report.delegations.iter().filter(|d| d.unlimited || d.delegate_kind.is_eoa()).count();
+    println!("\n  📊 Summary:");
+    println!("    Delegations: {} ({} risky)", report.delegations.len(), risky);
+    println!("    Third-party close authorities: {}", report.close_authorities.len());
+    match &report.owner_changes {
+        Ok(changes) => println!("    Owner changes: {}", changes.len()),
+        Err(_) => println!("    Owner changes: unknown (scan incomplete)"),
+    }
+    if risky > 0 {
+        println!("    ⚠️  Revoke approvals you don't recognise (spl-token revoke <ACCOUNT>)");
+    }
+}
@@ -1,9 +1,21 @@
+use crate::authority::{code_hash, load_program, without_padding, Loader, Program, V4Status};
+use crate::governance::{identify, Control, ControlKind};
+use crate::rules::{score, Finding, Hit, RuleSet};
+use crate::sbpf::{analyze, print_report, ElfReport};
 use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Code hash an ELF analysis was made for, and its outcome
+type Analysis = (String, Result<ElfReport, String>);
 
 /// Quick smart contract audit — detects dangerous patterns via account analysis
 pub struct ContractAudit {
     client: reqwest::Client,
     rpc_url: String,
+    rules: RuleSet,
+    /// Last ELF analysis per program, keyed by code hash: unchanged code isn't re-parsed
+    analyses: Mutex<HashMap<String, Analysis>>,
 }
 
 #[derive(Debug)]
@@ -13,96 +25,196 @@ pub struct AuditResult {
     pub is_upgradeable: bool,
     pub owner: String,
     pub data_size: usize,
-    pub warnings: Vec<String>,
+    /// Rules that fired; their weights add up to `risk_score`
+    pub findings: Vec<Finding>,
     pub risk_score: u8, // 0-100
+    pub upgrade_authority: Option<String>,
+    /// Who controls the upgrade authority, when it could be looked up
+    pub control: Option<Control>,
+    /// What the deployed ELF contains, when it could be read and parsed
+    pub elf: Option<ElfReport>,
+    /// The program account as its loader sees it (the ELF itself is left out)
+    pub program: Program,
+}
+
+impl AuditResult {
+    /// Finding evidence alone, for one-line-per-finding output
+    pub fn warnings(&self) -> Vec<&str> {
+        self.findings.iter().map(|f| f.evidence.as_str()).collect()
+    }
+}
+
+/// Deployed bytecode compared with a locally built artifact
+#[derive(Debug)]
+pub struct Verification {
+    pub artifact: String,
+    /// Account the code lives in: ProgramData for the upgradeable loader, else the program itself
+    pub programdata: String,
+    /// Legacy loaders don't record one
+    pub deploy_slot: Option<u64>,
+    pub deployed_hash: String,
+    pub deployed_len: usize,
+    pub local_hash: String,
+    pub local_len: usize,
+}
+
+impl Verification {
+    pub fn matched(&self) -> bool {
+        self.deployed_hash == self.local_hash
+    }
+
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({
+            "artifact": self.artifact,
+            "programdata": self.programdata,
+            "deploy_slot": self.deploy_slot,
+            "deployed_hash": self.deployed_hash,
+            "deployed_len": self.deployed_len,
+            "local_hash": self.local_hash,
+            "local_len": self.local_len,
+            "matched": self.matched(),
+        })
+    }
 }
 
 impl ContractAudit {
-    pub fn new(rpc_url: String) -> Self {
+    pub fn new(rpc_url: String, rules: RuleSet) -> Self {
         Self {
             client: reqwest::Client::new(),
             rpc_url,
+            rules,
+            analyses: Mutex::new(HashMap::new()),
         }
     }
 
     pub async fn audit(&self, program_id: &str) -> Result<AuditResult, Box<dyn std::error::Error>> {
-        let mut warnings = Vec::new();
-        let mut risk_score: u8 = 0;
-
-        // 1. Get account info
-        let body = serde_json::json!({
-            "jsonrpc": "2.0", "id": 1,
-            "method": "getAccountInfo",
-            "params": [program_id, { "encoding": "jsonParsed" }]
-        });
-        let resp: Value = self.client.post(&self.rpc_url).json(&body).send().await?.json().await?;
-        
-        let account = &resp["result"]["value"];
-        let is_executable = account["executable"].as_bool().unwrap_or(false);
-        let owner = account["owner"].as_str().unwrap_or("unknown").to_string();
-        let data_size = account["data"].as_array().map_or(0, |d| {
-            d.first().and_then(|v| v.as_str()).map_or(0, |s| s.len())
-        });
+        let program = load_program(&self.client, &self.rpc_url, program_id).await?;
+        Ok(self.audit_program(program_id, &program).await)
+    }
+
+    /// Audit a program account already decoded by `load_program`
+    pub async fn audit_program(&self, program_id: &str, program: &Program) -> AuditResult {
+        let mut hits = Vec::new();
 
+        // 1. The program account as its loader sees it
+        let is_executable = program.executable;
         if !is_executable {
-            warnings.push("⚠️  Not an executable program".to_string());
-            risk_score += 20;
+            hits.push(Hit::new("not_executable", "⚠️  Not an executable program".to_string()));
         }
 
-        // 2. Check if upgradeable (BPF Upgradeable Loader)
-        let is_upgradeable = owner == "BPFLoaderUpgradeab1e11111111111111111111111";
-        if is_upgradeable {
-            warnings.push("🔓 UPGRADEABLE — owner can change code at any time".to_string());
-            risk_score += 30;
-
-            // Check programdata account for upgrade authority
-            let body2 = serde_json::json!({
-                "jsonrpc": "2.0", "id": 1,
-                "method": "getAccountInfo",
-                "params": [program_id, { "encoding": "base64" }]
-            });
-            let resp2: Value = self.client.post(&self.rpc_url).json(&body2).send().await?.json().await?;
-            if let Some(data) = resp2["result"]["value"]["data"].as_array() {
-                if let Some(b64) = data.first().and_then(|v| v.as_str()) {
-                    if b64.len() < 100 {
-                        warnings.push("📦 Small program — likely a proxy/pointer".to_string());
-                        risk_score += 10;
-                    }
+        // 2. Who can change the code
+        let is_upgradeable = program.upgradeable();
+        let upgrade_authority = program.authority.clone();
+        let mut control = None;
+        match program.loader {
+            Some(Loader::Upgradeable | Loader::V4) => {
+                // A 1-of-1 hot key and a timelocked multisig are very different risks
+                if let Some(authority) = &upgrade_authority {
+                    control = identify(&self.rpc_url, authority).await.ok();
+                }
+                let evidence = |label: &str| format!("🔓 UPGRADEABLE — controlled by {}", label);
+                match &control {
+                    Some(c) => hits.push(match c.kind {
+                        ControlKind::HotKey => Hit::new("upgradeable_hot_key", evidence(&c.label())),
+                        ControlKind::UnknownPda => Hit::new("upgradeable_unknown_pda", evidence(&c.label())),
+                        ControlKind::SquadsV3 | ControlKind::SquadsV4 => Hit::weighted("upgradeable_multisig", c.upgrade_risk(), evidence(&c.label())),
+                        ControlKind::Governance => Hit::weighted("upgradeable_dao", c.upgrade_risk(), evidence(&c.label())),
+                    }),
+                    None if is_upgradeable => hits.push(Hit::new("upgradeable_unknown", "🔓 UPGRADEABLE — owner can change code at any time".to_string())),
+                    None if program.elf.is_none() => hits.push(Hit::new("closed", "🪦 Program closed — its code is gone and it can't be invoked".to_string())),
+                    None if program.loader == Some(Loader::V4) => hits.push(Hit::new("immutable", match &program.next_version {
+                        Some(next) => format!("🔒 Finalized — the code can no longer change (next version: {})", next),
+                        None => "🔒 Finalized — the code can no longer change".to_string(),
+                    })),
+                    None => hits.push(Hit::new("immutable", "🔒 Upgrade authority revoked — the code can no longer change".to_string())),
+                }
+                if program.status == Some(V4Status::Retracted) {
+                    hits.push(Hit::new("retracted", "⏸️  Retracted — the program can't be invoked until it is redeployed".to_string()));
                 }
             }
+            Some(Loader::Bpf1 | Loader::Bpf2) => hits.push(Hit::new("legacy_loader", format!("🔒 Deployed with the legacy {} — the code can never change", program.loader.map_or("", |l| l.name())))),
+            Some(Loader::Native) => hits.push(Hit::new("builtin", format!("⚙️  Builtin {}— changes only with validator releases and feature gates",
+                program.builtin.as_deref().map(|b| format!("{} ", b)).unwrap_or_default()))),
+            None => {}
         }
 
-        // 3. Check if it's a known program
-        let known_safe = vec![
-            "11111111111111111111111111111111",
-            "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
-            "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
-            "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
-        ];
-        if known_safe.contains(&program_id) {
-            warnings.clear();
-            warnings.push("✅ Known system program".to_string());
-            risk_score = 0;
+        // 3. Read the bytecode itself
+        let code = program.elf.as_deref();
+        let mut elf = None;
+        if let Some(code) = code {
+            match self.analysis(program_id, code) {
+                Ok(report) => {
+                    hits.extend(report.hits());
+                    elf = Some(report);
+                }
+                Err(e) => hits.push(Hit::new("invalid_elf", format!("❌ Deployed code is not a valid sBPF ELF: {}", e))),
+            }
         }
 
-        // 4. Check data size (very small = suspicious, very large = complex)
-        if data_size > 0 && data_size < 500 && is_executable {
-            warnings.push("🔍 Very small program — may be a proxy".to_string());
-            risk_score += 15;
+        // 4. Check code size (very small = suspicious, very large = complex)
+        let data_size = code.map_or(0, |c| without_padding(c).len());
+        if code.is_some() && data_size < 500 {
+            hits.push(Hit::new("tiny_program", "🔍 Very small program — may be a proxy".to_string()));
         }
         if data_size > 500_000 {
-            warnings.push("📏 Very large program (>500KB) — complex, more attack surface".to_string());
-            risk_score += 10;
+            hits.push(Hit::new("large_program", "📏 Very large program (>500KB) — complex, more attack surface".to_string()));
         }
 
-        Ok(AuditResult {
+        // 5. Weigh the hits with the team's rules; allowlisted programs skip them all
+        let findings = if self.rules.allowlisted(program_id) {
+            self.rules.evaluate(&[program_id], vec![Hit::new("allowlisted", "✅ Allowlisted program".to_string())])
+        } else {
+            let mut subjects = vec![program_id];
+            subjects.extend(upgrade_authority.as_deref());
+            self.rules.evaluate(&subjects, hits)
+        };
+
+        AuditResult {
             program_id: program_id.to_string(),
             is_executable,
             is_upgradeable,
-            owner,
+            owner: program.owner.clone(),
             data_size,
-            warnings,
-            risk_score: risk_score.min(100),
+            risk_score: score(&findings),
+            findings,
+            upgrade_authority,
+            control,
+            elf,
+            program: program.without_code(),
+        }
+    }
+
+    fn analysis(&self, program_id: &str, code: &[u8]) -> Result<ElfReport, String> {
+        let hash = code_hash(without_padding(code));
+        let mut analyses = self.analyses.lock().unwrap();
+        if let Some((known, report)) = analyses.get(program_id) {
+            if *known == hash {
+                return report.clone();
+            }
+        }
+        let report = analyze(code, program_id).map_err(|e| e.to_string());
+        analyses.insert(program_id.to_string(), (hash, report.clone()));
+        report
+    }
+
+    /// Hash the deployed ELF and the `.so` at `artifact` the same way and compare them
+    pub fn verify(&self, program_id: &str, program: &Program, artifact: &str) -> Result<Verification, Box<dyn std::error::Error>> {
+        let local = std::fs::read(artifact).map_err(|e| format!("Cannot read {}: {}", artifact, e))?;
+        let local = without_padding(&local);
+
+        let Some(elf) = &program.elf else {
+            return Err(format!("{} has no deployed code on chain ({})", program_id, program.mutability()).into());
+        };
+        let deployed = without_padding(elf);
+
+        Ok(Verification {
+            artifact: artifact.to_string(),
+            programdata: program.programdata.clone().unwrap_or_else(|| program_id.to_string()),
+            deploy_slot: program.deploy_slot,
+            deployed_hash: code_hash(deployed),
+            deployed_len: deployed.len(),
+            local_hash: code_hash(local),
+            local_len: local.len(),
         })
     }
 }
@@ -111,7 +223,19 @@ pub fn print_audit(result: &AuditResult) {
     println!("\n🛡️  Contract Audit: {}...{}", &result.program_id[..8], &result.program_id[result.program_id.len()-4..]);
     println!("═══════════════════════════════════════════");
     println!("  Executable: {}", if result.is_executable { "✅" } else { "❌" });
-    println!("  Upgradeable: {}", if result.is_upgradeable { "🔓 YES" } else { "🔒 NO" });
+    println!("  Upgradeable: {} — {}", if result.is_upgradeable { "🔓 YES" } else { "🔒 NO" }, result.program.mutability());
+    if let Some(loader) = result.program.loader {
+        println!("  Loader: {}{}", loader.name(), result.program.status.map(|s| format!(" ({})", s.name())).unwrap_or_default());
+    }
+    if let Some(authority) = &result.upgrade_authority {
+        println!("  Upgrade authority: {}", authority);
+    }
+    if let Some(control) = &result.control {
+        println!("  Controlled by: {}", control.label());
+        if let Some(account) = &control.account {
+            println!("    via {}", account);
+        }
+    }
     println!("  Owner: {}...{}", &result.owner[..8], &result.owner[result.owner.len()-4..]);
     println!("  Data size: {} bytes", result.data_size);
     
@@ -122,11 +246,28 @@ pub fn print_audit(result: &AuditResult) {
         _ => "🔴",
     };
     println!("  Risk score: {} {}/100", risk_emoji, result.risk_score);
+    if let Some(elf) = &result.elf {
+        print_report(elf);
+    }
     
-    if !result.warnings.is_empty() {
+    if !result.findings.is_empty() {
         println!("\n  Findings:");
-        for w in &result.warnings {
-            println!("    {}", w);
+        for f in &result.findings {
+            let weight = if f.weight > 0 { format!(" +{}", f.weight) } else { String::new() };
+            println!("    {}  [{} {}{}]", f.evidence, f.rule, f.severity.name(), weight);
         }
     }
 }
+
+pub fn print_verification(v: &Verification) {
+    println!("\n  Build verification:");
+    println!("    Artifact: {} ({} bytes)", v.artifact, v.local_len);
+    println!("    Local hash:    {}", v.local_hash);
+    println!("    Deployed hash: {} ({} bytes{})", v.deployed_hash, v.deployed_len,
+        v.deploy_slot.map(|slot| format!(", slot {}", slot)).unwrap_or_default());
+    if v.matched() {
+        println!("    ✅ MATCHED — the artifact is what's deployed");
+    } else {
+        println!("    ❌ MISMATCHED — the deployed code was not built from this artifact");
+    }
+}
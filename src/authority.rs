@@ -1,3 +1,6 @@
+use crate::crypto::{hex, sha256};
+use crate::encoding::{base64_decode, bs58_encode};
+use crate::governance::{get_account, identify, Control, ControlKind};
 use serde_json::Value;
 use std::collections::HashMap;
 
@@ -16,6 +19,236 @@ pub struct AuthorityInfo {
     pub upgrade_authority: Option<String>,
     pub authority_sol_balance: Option<f64>,
     pub authority_tx_count: Option<usize>,
+    /// Slot the current code was deployed at (from the ProgramData header)
+    pub deploy_slot: Option<u64>,
+    /// Hex SHA-256 of the deployed ELF
+    pub code_hash: Option<String>,
+    /// Who stands behind the upgrade authority (hot key, multisig, DAO)
+    pub control: Option<Control>,
+    /// Loader that owns the program account
+    pub loader: Option<Loader>,
+    /// How the code can change, e.g. "upgradeable" or "immutable (legacy loader)"
+    pub mutability: &'static str,
+}
+
+/// Owner of upgradeable programs, their ProgramData accounts and deploy buffers
+pub const UPGRADEABLE_LOADER: &str = "BPFLoaderUpgradeab1e11111111111111111111111";
+
+/// ProgramData account: [4 bytes type][8 bytes slot][1 byte option][32 bytes authority], then the ELF
+pub const PROGRAMDATA_HEADER_LEN: usize = 45;
+
+/// `elf` up to its last non-zero byte, dropping the zero padding left over from a larger
+/// allocation. Local build artifacts are trimmed the same way so their hashes compare.
+pub fn without_padding(elf: &[u8]) -> &[u8] {
+    let end = elf.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    &elf[..end]
+}
+
+pub fn code_hash(elf: &[u8]) -> String {
+    hex(&sha256(elf))
+}
+
+/// Loader v4 program account: [8 bytes slot][32 bytes authority or next version][8 bytes status], then the ELF
+pub const LOADER_V4_HEADER_LEN: usize = 48;
+
+/// Loaders that own program accounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Loader {
+    /// Builtins and precompiles, compiled into the validator
+    Native,
+    /// BPFLoader1 (deprecated) and BPFLoader2: code lives in the program account and never changes
+    Bpf1,
+    Bpf2,
+    /// Code in a separate ProgramData account, replaceable by the upgrade authority
+    Upgradeable,
+    /// Code in the program account, replaceable by its authority until finalized
+    V4,
+}
+
+impl Loader {
+    pub fn from_owner(owner: &str) -> Option<Self> {
+        match owner {
+            "NativeLoader1111111111111111111111111111111" => Some(Self::Native),
+            "BPFLoader1111111111111111111111111111111111" => Some(Self::Bpf1),
+            "BPFLoader2111111111111111111111111111111111" => Some(Self::Bpf2),
+            UPGRADEABLE_LOADER => Some(Self::Upgradeable),
+            "LoaderV411111111111111111111111111111111111" => Some(Self::V4),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Native => "native",
+            Self::Bpf1 => "bpf_loader_deprecated",
+            Self::Bpf2 => "bpf_loader",
+            Self::Upgradeable => "bpf_loader_upgradeable",
+            Self::V4 => "loader_v4",
+        }
+    }
+}
+
+/// Loader v4 program status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum V4Status {
+    /// Under maintenance: the code can be replaced but the program can't be invoked
+    Retracted,
+    Deployed,
+    /// Immutable for good
+    Finalized,
+}
+
+impl V4Status {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Retracted => "retracted",
+            Self::Deployed => "deployed",
+            Self::Finalized => "finalized",
+        }
+    }
+}
+
+/// A program account decoded according to the loader that owns it
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub owner: String,
+    pub executable: bool,
+    pub loader: Option<Loader>,
+    /// ProgramData account holding the code (upgradeable loader only)
+    pub programdata: Option<String>,
+    /// Key that can replace the code; None when no key can
+    pub authority: Option<String>,
+    /// Slot the current code was deployed at
+    pub deploy_slot: Option<u64>,
+    pub status: Option<V4Status>,
+    /// Successor a finalized loader v4 program points to
+    pub next_version: Option<String>,
+    /// Builtin name a native program's account holds, e.g. "system_program"
+    pub builtin: Option<String>,
+    /// Deployed ELF with its zero padding: trimming it could cut into the section header table
+    pub elf: Option<Vec<u8>>,
+}
+
+impl Program {
+    pub fn upgradeable(&self) -> bool {
+        self.authority.is_some()
+    }
+
+    /// How the code can change
+    pub fn mutability(&self) -> &'static str {
+        match (self.loader, &self.authority) {
+            (Some(Loader::V4), Some(_)) if self.status == Some(V4Status::Retracted) => "upgradeable (retracted)",
+            (Some(Loader::Upgradeable | Loader::V4), Some(_)) => "upgradeable",
+            (Some(Loader::Upgradeable), None) if self.elf.is_none() => "closed",
+            (Some(Loader::Upgradeable), None) => "immutable (authority revoked)",
+            (Some(Loader::V4), None) => "immutable (finalized)",
+            (Some(Loader::Bpf1 | Loader::Bpf2), _) => "immutable (legacy loader)",
+            (Some(Loader::Native), _) => "builtin (changes with validator releases)",
+            (None, _) => "not a program",
+        }
+    }
+
+    pub fn code_hash(&self) -> Option<String> {
+        self.elf.as_deref().map(|elf| code_hash(without_padding(elf)))
+    }
+
+    /// Everything but the ELF, for keeping around after the code has been read
+    pub fn without_code(&self) -> Program {
+        Program {
+            owner: self.owner.clone(),
+            executable: self.executable,
+            loader: self.loader,
+            programdata: self.programdata.clone(),
+            authority: self.authority.clone(),
+            deploy_slot: self.deploy_slot,
+            status: self.status,
+            next_version: self.next_version.clone(),
+            builtin: self.builtin.clone(),
+            elf: None,
+        }
+    }
+}
+
+/// Fetch a program account, and its ProgramData account for the upgradeable loader, and decode
+/// where the code lives and who can change it
+pub async fn load_program(client: &reqwest::Client, rpc_url: &str, program_id: &str) -> Result<Program, Box<dyn std::error::Error>> {
+    let body = serde_json::json!({
+        "jsonrpc": "2.0", "id": 1,
+        "method": "getAccountInfo",
+        "params": [program_id, { "encoding": "base64" }]
+    });
+    let resp: Value = client.post(rpc_url).json(&body).send().await?.json().await?;
+    if let Some(err) = resp.get("error") {
+        return Err(format!("RPC error: {}", err).into());
+    }
+    let account = &resp["result"]["value"];
+    if account.is_null() {
+        return Err(format!("Account {} not found", program_id).into());
+    }
+    let owner = account["owner"].as_str().unwrap_or("unknown").to_string();
+    let data = base64_decode(account["data"].as_array()
+        .and_then(|d| d.first())
+        .and_then(|v| v.as_str())
+        .unwrap_or(""))?;
+
+    let loader = Loader::from_owner(&owner);
+    let mut program = Program {
+        owner,
+        executable: account["executable"].as_bool().unwrap_or(false),
+        loader,
+        programdata: None,
+        authority: None,
+        deploy_slot: None,
+        status: None,
+        next_version: None,
+        builtin: None,
+        elf: None,
+    };
+    let pubkey = |bytes: &[u8]| (bytes.iter().any(|b| *b != 0)).then(|| bs58_encode(bytes));
+    match loader {
+        Some(Loader::Upgradeable) => {
+            // Program account: [4 bytes type = 2][32 bytes programdata pubkey]
+            let Some(address) = data.get(4..36).filter(|_| data[..4] == [2, 0, 0, 0]) else { return Ok(program) };
+            let address = bs58_encode(address);
+            // Close drains the ProgramData account (gone, or Uninitialized until it is purged). Anything
+            // else unreadable is an error, so a flaky RPC can't make a live program look closed.
+            match get_account(client, rpc_url, &address).await? {
+                None => {}
+                Some((_, pd)) if pd.get(..4) == Some(&[0, 0, 0, 0]) => {}
+                Some((_, pd)) if pd.len() >= PROGRAMDATA_HEADER_LEN && pd[..4] == [3, 0, 0, 0] => {
+                    program.deploy_slot = Some(u64::from_le_bytes(pd[4..12].try_into().unwrap()));
+                    if pd[12] == 1 {
+                        program.authority = Some(bs58_encode(&pd[13..45]));
+                    }
+                    program.elf = Some(pd[PROGRAMDATA_HEADER_LEN..].to_vec());
+                }
+                Some(_) => return Err(format!("ProgramData account {} is not readable", address).into()),
+            }
+            program.programdata = Some(address);
+        }
+        Some(Loader::V4) if data.len() >= LOADER_V4_HEADER_LEN => {
+            program.deploy_slot = Some(u64::from_le_bytes(data[0..8].try_into().unwrap()));
+            let key = &data[8..40];
+            program.status = match u64::from_le_bytes(data[40..48].try_into().unwrap()) {
+                0 => Some(V4Status::Retracted),
+                1 => Some(V4Status::Deployed),
+                2 => Some(V4Status::Finalized),
+                _ => None,
+            };
+            if program.status == Some(V4Status::Finalized) {
+                program.next_version = pubkey(key).filter(|next| next != program_id);
+            } else {
+                program.authority = pubkey(key);
+            }
+            program.elf = Some(data[LOADER_V4_HEADER_LEN..].to_vec());
+        }
+        Some(Loader::Bpf1 | Loader::Bpf2) => program.elf = Some(data),
+        Some(Loader::Native) => {
+            program.builtin = std::str::from_utf8(&data).ok().map(|name| name.trim_end_matches('\0').to_string());
+        }
+        _ => {}
+    }
+    Ok(program)
 }
 
 impl AuthorityMapper {
@@ -24,85 +257,38 @@ impl AuthorityMapper {
     }
 
     pub async fn map_authority(&self, program_id: &str, name: &str) -> Result<AuthorityInfo, Box<dyn std::error::Error>> {
-        // Step 1: Get program account to find programdata address
-        let body = serde_json::json!({
-            "jsonrpc": "2.0", "id": 1,
-            "method": "getAccountInfo",
-            "params": [program_id, { "encoding": "base64" }]
-        });
-        let resp: Value = self.client.post(&self.rpc_url).json(&body).send().await?.json().await?;
-        
-        let owner = resp["result"]["value"]["owner"].as_str().unwrap_or("");
-        
-        if owner != "BPFLoaderUpgradeab1e11111111111111111111111" {
-            return Ok(AuthorityInfo {
-                program_id: program_id.to_string(),
-                program_name: name.to_string(),
-                programdata_account: None,
-                upgrade_authority: None,
-                authority_sol_balance: None,
-                authority_tx_count: None,
-            });
-        }
+        // Step 1: Decode the program account for its loader; upgradeable programs also need their ProgramData account
+        let program = load_program(&self.client, &self.rpc_url, program_id).await?;
 
-        // Step 2: Decode programdata address from program account data
-        // For BPF Upgradeable, program account data = [4 bytes type][32 bytes programdata pubkey]
-        let data_b64 = resp["result"]["value"]["data"]
-            .as_array()
-            .and_then(|a| a.first())
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        
-        let programdata_addr = if !data_b64.is_empty() {
-            if let Ok(bytes) = base64_decode(data_b64) {
-                if bytes.len() >= 36 {
-                    Some(bs58_encode(&bytes[4..36]))
-                } else { None }
-            } else { None }
-        } else { None };
-
-        // Step 3: Get programdata account to find upgrade authority
-        let upgrade_authority = if let Some(ref pda) = programdata_addr {
-            let body2 = serde_json::json!({
-                "jsonrpc": "2.0", "id": 1,
-                "method": "getAccountInfo",
-                "params": [pda, { "encoding": "base64" }]
-            });
-            let resp2: Value = self.client.post(&self.rpc_url).json(&body2).send().await?.json().await?;
-            
-            let pda_data = resp2["result"]["value"]["data"]
-                .as_array()
-                .and_then(|a| a.first())
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            
-            if !pda_data.is_empty() {
-                if let Ok(bytes) = base64_decode(pda_data) {
-                    // ProgramData account: [4 bytes type][8 bytes slot][1 byte option][32 bytes authority]
-                    if bytes.len() >= 45 && bytes[12] == 1 {
-                        Some(bs58_encode(&bytes[13..45]))
-                    } else { None }
-                } else { None }
-            } else { None }
-        } else { None };
-
-        // Step 4: Get authority wallet info
-        let (authority_sol_balance, authority_tx_count) = if let Some(ref auth) = upgrade_authority {
-            let bal = self.get_balance(auth).await.unwrap_or(0.0);
-            let txs = self.get_sig_count(auth).await.unwrap_or(0);
-            (Some(bal), Some(txs))
+        // Step 2: What kind of signer the authority is
+        let control = match &program.authority {
+            Some(auth) => identify(&self.rpc_url, auth).await.ok(),
+            None => None,
+        };
+        Ok(self.map_program(program_id, name, &program, control).await)
+    }
+
+    /// Authority info for a program already loaded, whose authority's `control` is already known
+    pub async fn map_program(&self, program_id: &str, name: &str, program: &Program, control: Option<Control>) -> AuthorityInfo {
+        let (authority_sol_balance, authority_tx_count) = if let Some(ref auth) = program.authority {
+            (self.get_balance(auth).await.ok(), Some(self.get_sig_count(auth).await.unwrap_or(0)))
         } else {
             (None, None)
         };
 
-        Ok(AuthorityInfo {
+        AuthorityInfo {
             program_id: program_id.to_string(),
             program_name: name.to_string(),
-            programdata_account: programdata_addr,
-            upgrade_authority,
+            code_hash: program.code_hash(),
+            mutability: program.mutability(),
+            loader: program.loader,
+            programdata_account: program.programdata.clone(),
+            upgrade_authority: program.authority.clone(),
             authority_sol_balance,
             authority_tx_count,
-        })
+            deploy_slot: program.deploy_slot,
+            control,
+        }
     }
 
     async fn get_balance(&self, addr: &str) -> Result<f64, Box<dyn std::error::Error>> {
@@ -112,7 +298,9 @@ impl AuthorityMapper {
             "params": [addr, {"commitment": "confirmed"}]
         });
         let resp: Value = self.client.post(&self.rpc_url).json(&body).send().await?.json().await?;
-        Ok(resp["result"]["value"].as_u64().unwrap_or(0) as f64 / 1_000_000_000.0)
+        let lamports = resp["result"]["value"].as_u64()
+            .ok_or_else(|| format!("getBalance failed for {}", addr))?;
+        Ok(lamports as f64 / 1_000_000_000.0)
     }
 
     async fn get_sig_count(&self, addr: &str) -> Result<usize, Box<dyn std::error::Error>> {
@@ -126,68 +314,18 @@ impl AuthorityMapper {
     }
 }
 
-fn base64_decode(input: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    use std::io::Read;
-    // Simple base64 decoder
-    let table = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-    let mut out = Vec::new();
-    let mut buf: u32 = 0;
-    let mut bits: u32 = 0;
-    for &c in input.as_bytes() {
-        if c == b'=' || c == b'\n' || c == b'\r' { continue; }
-        let val = table.iter().position(|&t| t == c).ok_or("invalid base64")? as u32;
-        buf = (buf << 6) | val;
-        bits += 6;
-        if bits >= 8 {
-            bits -= 8;
-            out.push(((buf >> bits) & 0xFF) as u8);
-        }
-    }
-    Ok(out)
-}
-
-fn bs58_encode(bytes: &[u8]) -> String {
-    const ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
-    if bytes.is_empty() { return String::new(); }
-    
-    let mut digits = vec![0u8];
-    for &byte in bytes {
-        let mut carry = byte as u32;
-        for d in digits.iter_mut() {
-            carry += (*d as u32) * 256;
-            *d = (carry % 58) as u8;
-            carry /= 58;
-        }
-        while carry > 0 {
-            digits.push((carry % 58) as u8);
-            carry /= 58;
-        }
-    }
-    
-    // Leading zeros
-    let mut result = String::new();
-    for &b in bytes {
-        if b == 0 { result.push('1'); } else { break; }
-    }
-    
-    for &d in digits.iter().rev() {
-        result.push(ALPHABET[d as usize] as char);
-    }
-    result
-}
-
-pub async fn map_all_authorities(rpc_url: &str) -> Vec<AuthorityInfo> {
+pub async fn map_all_authorities(rpc_url: &str, programs: &[crate::watchlist::WatchedProgram]) -> Vec<AuthorityInfo> {
     let mapper = AuthorityMapper::new(rpc_url.to_string());
     let mut results = Vec::new();
     
-    for (program_id, name) in crate::scanner::PROGRAMS {
+    for crate::watchlist::WatchedProgram { id: program_id, name, .. } in programs {
         eprint!("  🔑 {}... ", name);
         match mapper.map_authority(program_id, name).await {
             Ok(info) => {
                 if let Some(ref auth) = info.upgrade_authority {
                     eprintln!("authority: {}...{}", &auth[..8], &auth[auth.len()-4..]);
                 } else {
-                    eprintln!("immutable ✅");
+                    eprintln!("{}", info.mutability);
                 }
                 results.push(info);
             }
@@ -206,22 +344,29 @@ pub fn print_power_map(results: &[AuthorityInfo]) {
     let mut authority_protocols: HashMap<String, Vec<String>> = HashMap::new();
     
     for info in results {
-        let key = info.upgrade_authority.clone().unwrap_or_else(|| "IMMUTABLE".to_string());
-        authority_protocols.entry(key).or_default().push(info.program_name.clone());
+        if let Some(key) = &info.upgrade_authority {
+            authority_protocols.entry(key.clone()).or_default().push(info.program_name.clone());
+        }
     }
     
     println!("\n  📋 Per-Protocol:");
     for info in results {
         match &info.upgrade_authority {
             Some(auth) => {
-                println!("    {} → 🔓 {}...{} ({:.2} SOL, {} txs)",
+                println!("    {} → 🔓 {}...{} ({} SOL, {} txs)",
                     info.program_name,
                     &auth[..8], &auth[auth.len()-4..],
-                    info.authority_sol_balance.unwrap_or(0.0),
+                    info.authority_sol_balance.map(|b| format!("{:.2}", b)).unwrap_or_else(|| "?".to_string()),
                     info.authority_tx_count.unwrap_or(0));
+                if let Some(control) = &info.control {
+                    println!("        {} {}", if control.kind == ControlKind::HotKey { "🔥" } else { "👥" }, control.label());
+                }
+            }
+            None if info.loader == Some(Loader::Native) => {
+                println!("    {} → ⚙️  {}", info.program_name, info.mutability);
             }
             None => {
-                println!("    {} → 🔒 IMMUTABLE", info.program_name);
+                println!("    {} → 🔒 {}", info.program_name, info.mutability.to_uppercase());
             }
         }
     }
@@ -229,7 +374,7 @@ pub fn print_power_map(results: &[AuthorityInfo]) {
     // Power concentration
     println!("\n  🏛️  Authority Concentration:");
     let mut sorted: Vec<_> = authority_protocols.iter().collect();
-    sorted.sort_by(|a, b| b.1.len().cmp(&a.1.len()));
+    sorted.sort_by_key(|(_, protocols)| std::cmp::Reverse(protocols.len()));
     
     for (auth, protocols) in &sorted {
         if protocols.len() > 1 {
@@ -240,10 +385,22 @@ pub fn print_power_map(results: &[AuthorityInfo]) {
         }
     }
     
-    let immutable = results.iter().filter(|r| r.upgrade_authority.is_none()).count();
+    let immutable = results.iter().filter(|r| r.mutability.starts_with("immutable")).count();
+    let builtin = results.iter().filter(|r| r.loader == Some(Loader::Native)).count();
     let total = results.len();
     println!("\n  📊 Summary:");
     println!("    Total protocols: {}", total);
     println!("    Immutable: {}/{} ({:.0}%)", immutable, total, immutable as f64 / total as f64 * 100.0);
+    if builtin > 0 {
+        println!("    Builtins: {}", builtin);
+    }
     println!("    Unique authorities: {}", authority_protocols.len());
+    let controlled_by = |kinds: &[ControlKind]| results.iter()
+        .filter(|r| r.control.as_ref().is_some_and(|c| kinds.contains(&c.kind)))
+        .count();
+    println!("    Hot keys: {} | Multisigs: {} | DAOs: {} | Other PDAs: {}",
+        controlled_by(&[ControlKind::HotKey]),
+        controlled_by(&[ControlKind::SquadsV3, ControlKind::SquadsV4]),
+        controlled_by(&[ControlKind::Governance]),
+        controlled_by(&[ControlKind::UnknownPda]));
 }
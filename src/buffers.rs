@@ -0,0 +1,125 @@
+use crate::authority::{code_hash, UPGRADEABLE_LOADER};
+use crate::encoding::{base64_decode, bs58_encode};
+use crate::sbpf::declared_len;
+use serde_json::Value;
+
+/// Buffer account: [4 bytes type = 1][1 byte option][32 bytes authority], then the ELF being written
+pub const BUFFER_HEADER_LEN: usize = 37;
+/// Size of one ELF64 section header
+const ELF64_SHDR_LEN: usize = 64;
+
+/// A deploy buffer: where an authority stages new code before `upgrade` swaps it in
+#[derive(Clone, Debug)]
+pub struct Buffer {
+    pub address: String,
+    pub lamports: u64,
+    /// Bytes allocated for the ELF
+    pub capacity: usize,
+    /// Bytes written so far (up to the last non-zero byte)
+    pub written: usize,
+    /// Length of the ELF according to its header, once the header has been written
+    pub elf_len: Option<usize>,
+    /// Hex SHA-256 of the written bytes; comparable to `authority::code_hash` once fully written
+    pub code_hash: String,
+}
+
+impl Buffer {
+    fn parse(address: &str, lamports: u64, data: &[u8]) -> Option<Self> {
+        let elf = data.get(BUFFER_HEADER_LEN..)?;
+        let written = elf.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+        Some(Self {
+            address: address.to_string(),
+            lamports,
+            capacity: elf.len(),
+            written,
+            elf_len: declared_len(elf),
+            code_hash: code_hash(&elf[..written]),
+        })
+    }
+
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({
+            "address": self.address,
+            "lamports": self.lamports,
+            "capacity": self.capacity,
+            "written": self.written,
+            "elf_len": self.elf_len,
+            "code_hash": self.code_hash,
+        })
+    }
+
+    pub fn from_json(json: &Value) -> Option<Self> {
+        Some(Self {
+            address: json["address"].as_str()?.to_string(),
+            lamports: json["lamports"].as_u64().unwrap_or(0),
+            capacity: json["capacity"].as_u64().unwrap_or(0) as usize,
+            written: json["written"].as_u64()? as usize,
+            elf_len: json["elf_len"].as_u64().map(|l| l as usize),
+            code_hash: json["code_hash"].as_str()?.to_string(),
+        })
+    }
+
+    /// Whether the whole ELF is there: the last byte written falls in the final section
+    /// header, which ends the file. Trailing zeros of the ELF itself read as padding, so
+    /// `written` alone can't tell.
+    fn complete(&self) -> bool {
+        self.elf_len.is_some_and(|len| len <= self.capacity && self.written + ELF64_SHDR_LEN > len)
+    }
+
+    /// How the staged code relates to what's deployed
+    pub fn compare(&self, deployed_hash: Option<&str>) -> &'static str {
+        if deployed_hash == Some(self.code_hash.as_str()) {
+            "same as deployed code"
+        } else if !self.complete() {
+            "still being written"
+        } else {
+            "DIFFERS from deployed code"
+        }
+    }
+}
+
+/// Buffer accounts whose authority is `authority`, i.e. code it could deploy next
+pub async fn find_buffers(rpc_url: &str, authority: &str) -> Result<Vec<Buffer>, Box<dyn std::error::Error>> {
+    let body = serde_json::json!({
+        "jsonrpc": "2.0", "id": 1,
+        "method": "getProgramAccounts",
+        "params": [UPGRADEABLE_LOADER, {
+            "encoding": "base64",
+            "filters": [
+                { "memcmp": { "offset": 0, "bytes": bs58_encode(&1u32.to_le_bytes()) } },
+                { "memcmp": { "offset": 4, "bytes": bs58_encode(&[1]) } },
+                { "memcmp": { "offset": 5, "bytes": authority } },
+            ],
+        }],
+    });
+    let resp: Value = reqwest::Client::new().post(rpc_url).json(&body).send().await?.json().await?;
+    if let Some(err) = resp.get("error") {
+        return Err(format!("getProgramAccounts: {}", err["message"].as_str().unwrap_or("RPC error")).into());
+    }
+
+    let mut buffers = Vec::new();
+    for acc in resp["result"].as_array().into_iter().flatten() {
+        let address = acc["pubkey"].as_str().unwrap_or("");
+        let data = acc["account"]["data"].as_array()
+            .and_then(|d| d.first())
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        if let Some(buffer) = Buffer::parse(address, acc["account"]["lamports"].as_u64().unwrap_or(0), &base64_decode(data)?) {
+            buffers.push(buffer);
+        }
+    }
+    buffers.sort_by(|a, b| a.address.cmp(&b.address));
+    Ok(buffers)
+}
+
+pub fn print_buffers(buffers: &[Buffer], deployed_hash: Option<&str>) {
+    println!("\n  Pending deploy buffers:");
+    if buffers.is_empty() {
+        println!("    none ✅");
+        return;
+    }
+    for b in buffers {
+        println!("    📦 {} — {}/{} bytes, {:.4} SOL", b.address, b.written, b.capacity, b.lamports as f64 / 1e9);
+        println!("       hash {} ({})", &b.code_hash[..16], b.compare(deployed_hash));
+    }
+}
@@ -0,0 +1,246 @@
+/// SHA-256 and program-derived addresses, implemented locally to keep the dependency list short
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut h: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+    ];
+
+    let mut msg = data.to_vec();
+    msg.push(0x80);
+    while msg.len() % 64 != 56 {
+        msg.push(0);
+    }
+    msg.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in msg.chunks(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i-15].rotate_right(7) ^ w[i-15].rotate_right(18) ^ (w[i-15] >> 3);
+            let s1 = w[i-2].rotate_right(17) ^ w[i-2].rotate_right(19) ^ (w[i-2] >> 10);
+            w[i] = w[i-16].wrapping_add(s0).wrapping_add(w[i-7]).wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = hh.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            hh = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (state, v) in h.iter_mut().zip([a, b, c, d, e, f, g, hh]) {
+            *state = state.wrapping_add(v);
+        }
+    }
+
+    let mut out = [0u8; 32];
+    for (i, word) in h.iter().enumerate() {
+        out[i*4..i*4+4].copy_from_slice(&word.to_be_bytes());
+    }
+    out
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// === Program-derived addresses ===
+
+/// Field element mod p = 2^255 - 19, little-endian 64-bit limbs
+type Fe = [u64; 4];
+
+const P: Fe = [0xffff_ffff_ffff_ffed, 0xffff_ffff_ffff_ffff, 0xffff_ffff_ffff_ffff, 0x7fff_ffff_ffff_ffff];
+/// Edwards curve constant d = -121665/121666
+const D: Fe = [0x75eb_4dca_1359_78a3, 0x0070_0a4d_4141_d8ab, 0x8cc7_4079_7779_e898, 0x5203_6cee_2b6f_fe73];
+
+fn fe_geq_p(a: &Fe) -> bool {
+    for i in (0..4).rev() {
+        if a[i] != P[i] { return a[i] > P[i]; }
+    }
+    true
+}
+
+fn fe_sub_p(a: &mut Fe) {
+    let mut borrow = 0u64;
+    for i in 0..4 {
+        let (d1, b1) = a[i].overflowing_sub(P[i]);
+        let (d2, b2) = d1.overflowing_sub(borrow);
+        a[i] = d2;
+        borrow = (b1 || b2) as u64;
+    }
+}
+
+/// Fold a 256-bit value plus `carry` * 2^256 back below p (2^256 ≡ 38)
+fn fe_normalize(mut r: Fe, mut carry: u128) -> Fe {
+    while carry > 0 {
+        let mut c = carry * 38;
+        for limb in r.iter_mut() {
+            c += *limb as u128;
+            *limb = c as u64;
+            c >>= 64;
+        }
+        carry = c;
+    }
+    while fe_geq_p(&r) {
+        fe_sub_p(&mut r);
+    }
+    r
+}
+
+fn fe_add(a: &Fe, b: &Fe) -> Fe {
+    let mut r = [0u64; 4];
+    let mut c = 0u128;
+    for i in 0..4 {
+        c += a[i] as u128 + b[i] as u128;
+        r[i] = c as u64;
+        c >>= 64;
+    }
+    fe_normalize(r, c)
+}
+
+fn fe_mul(a: &Fe, b: &Fe) -> Fe {
+    let mut t = [0u64; 8];
+    for i in 0..4 {
+        let mut c = 0u128;
+        for j in 0..4 {
+            c += t[i+j] as u128 + a[i] as u128 * b[j] as u128;
+            t[i+j] = c as u64;
+            c >>= 64;
+        }
+        t[i+4] = c as u64;
+    }
+    let mut r = [0u64; 4];
+    let mut c = 0u128;
+    for i in 0..4 {
+        c += t[i] as u128 + t[i+4] as u128 * 38;
+        r[i] = c as u64;
+        c >>= 64;
+    }
+    fe_normalize(r, c)
+}
+
+fn fe_pow(base: &Fe, exp: &Fe) -> Fe {
+    let mut result: Fe = [1, 0, 0, 0];
+    for i in (0..256).rev() {
+        result = fe_mul(&result, &result);
+        if (exp[i / 64] >> (i % 64)) & 1 == 1 {
+            result = fe_mul(&result, base);
+        }
+    }
+    result
+}
+
+/// Whether 32 bytes decompress to an ed25519 point (PDAs must not)
+pub fn is_on_curve(bytes: &[u8; 32]) -> bool {
+    let mut y: Fe = [0; 4];
+    for (i, limb) in y.iter_mut().enumerate() {
+        *limb = u64::from_le_bytes(bytes[i*8..i*8+8].try_into().unwrap());
+    }
+    y[3] &= 0x7fff_ffff_ffff_ffff;
+    let y = fe_normalize(y, 0);
+
+    // x² = (y² - 1) / (d·y² + 1) has a solution iff (y² - 1)(d·y² + 1) is a square
+    let y2 = fe_mul(&y, &y);
+    let p_minus_1: Fe = [P[0] - 1, P[1], P[2], P[3]];
+    let u = fe_add(&y2, &p_minus_1);
+    let v = fe_add(&fe_mul(&D, &y2), &[1, 0, 0, 0]);
+    let uv = fe_mul(&u, &v);
+
+    // Euler's criterion: (uv)^((p-1)/2) is 1 for squares, 0 for zero
+    let half: Fe = [0xffff_ffff_ffff_fff6, P[1], P[2], 0x3fff_ffff_ffff_ffff];
+    let chi = fe_pow(&uv, &half);
+    chi == [1, 0, 0, 0] || chi == [0, 0, 0, 0]
+}
+
+pub fn create_program_address(seeds: &[&[u8]], program_id: &[u8; 32]) -> Option<[u8; 32]> {
+    let mut data = Vec::new();
+    for seed in seeds {
+        data.extend_from_slice(seed);
+    }
+    data.extend_from_slice(program_id);
+    data.extend_from_slice(b"ProgramDerivedAddress");
+    let hash = sha256(&data);
+    (!is_on_curve(&hash)).then_some(hash)
+}
+
+pub fn find_program_address(seeds: &[&[u8]], program_id: &[u8; 32]) -> ([u8; 32], u8) {
+    for bump in (0..=255u8).rev() {
+        let bump_seed = [bump];
+        let mut with_bump: Vec<&[u8]> = seeds.to_vec();
+        with_bump.push(&bump_seed);
+        if let Some(address) = create_program_address(&with_bump, program_id) {
+            return (address, bump);
+        }
+    }
+    // Unreachable in practice: ~50% of hashes are off-curve
+    ([0; 32], 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::{bs58_encode, pubkey_bytes};
+
+    #[test]
+    fn sha256_nist_vectors() {
+        assert_eq!(hex(&sha256(b"")), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(hex(&sha256(b"abc")), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(
+            hex(&sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        assert_eq!(
+            hex(&sha256(&vec![b'a'; 1_000_000])),
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+    }
+
+    #[test]
+    fn on_curve() {
+        // The ed25519 base point (y = 4/5) and a wallet address are points; a PDA isn't
+        let mut base = [0x66; 32];
+        base[0] = 0x58;
+        assert!(is_on_curve(&base));
+        assert!(is_on_curve(&pubkey_bytes("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM").unwrap()));
+        assert!(!is_on_curve(&pubkey_bytes("4Ec7ZxZS6Sbdg5UGSLHbAnM7GQHp2eFd4KYWRexAipQT").unwrap()));
+    }
+
+    #[test]
+    fn program_addresses() {
+        let loader = pubkey_bytes("BPFLoaderUpgradeab1e11111111111111111111111").unwrap();
+        let program = pubkey_bytes("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4").unwrap();
+        let (programdata, _) = find_program_address(&[&program], &loader);
+        assert_eq!(bs58_encode(&programdata), "4Ec7ZxZS6Sbdg5UGSLHbAnM7GQHp2eFd4KYWRexAipQT");
+
+        // Associated token account of a wallet for USDC
+        let wallet = pubkey_bytes("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM").unwrap();
+        let token = pubkey_bytes("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+        let mint = pubkey_bytes("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+        let ata_program = pubkey_bytes("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL").unwrap();
+        let (ata, bump) = find_program_address(&[&wallet, &token, &mint], &ata_program);
+        assert_eq!(bs58_encode(&ata), "FGETo8T8wMcN2wCjav8VK6eh3dLk63evNDPxzLSJra8B");
+        assert_eq!(bump, 254);
+    }
+}
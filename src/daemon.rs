@@ -1,30 +1,114 @@
+use crate::alerts::AlertRouter;
+use crate::api::{SharedStatus, StatusBoard};
+use crate::crypto::{hex, sha256};
+use crate::incidents::IncidentTracker;
 use crate::audit::ContractAudit;
-use crate::scanner::PROGRAMS;
-use std::collections::HashMap;
+use crate::authority::load_program;
+use crate::activity::{poll as poll_activity, AuthorityActivity, DORMANT_DAYS};
+use crate::buffers::{find_buffers, Buffer};
+use crate::proposals::{find as find_proposals, Proposal, Stage, Target};
+use crate::rules::RuleSet;
+use crate::sbpf::SecurityTxt;
+use crate::watchlist::{Source, WatchedProgram};
+use crate::store::{GuardianStore, SavedState};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 
 /// Autonomous monitoring daemon
 /// Watches DeFi protocols for authority changes, upgrade events, anomalies
 pub struct Daemon {
     rpc_url: String,
+    /// Scores programs by its rules; keeps each program's ELF analysis until the code changes
+    auditor: ContractAudit,
+    programs: Vec<WatchedProgram>,
+    /// Default check interval; programs may override it
+    interval_secs: u64,
+    next_check: HashMap<String, std::time::Instant>,
     /// Last known state of each program
     last_state: HashMap<String, ProgramState>,
+    /// Programs whose last state was loaded from disk and not yet re-checked
+    restored: HashSet<String>,
     /// Alerts generated
     pub alerts: Vec<Alert>,
+    /// Failed audit/authority lookups since start
+    pub rpc_errors: u64,
+    /// Latest audit risk score per program
+    pub risk_scores: HashMap<String, u8>,
+    /// Open alerts, for dedup, cooldown and resolution
+    pub incidents: IncidentTracker,
 }
 
 #[derive(Clone, Debug)]
-struct ProgramState {
-    is_upgradeable: bool,
-    authority: Option<String>,
-    authority_balance: f64,
-    data_size: usize,
+pub struct ProgramState {
+    pub is_upgradeable: bool,
+    pub authority: Option<String>,
+    /// None when the balance couldn't be read
+    pub authority_balance: Option<f64>,
+    /// Slot of the last deploy, from the ProgramData header
+    pub deploy_slot: Option<u64>,
+    /// Hex SHA-256 of the deployed ELF
+    pub code_hash: Option<String>,
+    /// Deploy buffers held by the upgrade authority
+    pub buffers: Vec<Buffer>,
+    /// Where the upgrade authority's transaction history was last read up to
+    pub activity: Option<AuthorityActivity>,
+    /// Multisig/DAO proposals touching the program; None until the controller has been checked
+    pub proposals: Option<Vec<Proposal>>,
+    /// security.txt embedded in the deployed code
+    pub security_txt: Option<SecurityTxt>,
+    /// Code hash `security_txt` was read from; lags `code_hash` while the new code can't be parsed
+    pub security_txt_hash: Option<String>,
+}
+
+impl ProgramState {
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "is_upgradeable": self.is_upgradeable,
+            "authority": self.authority,
+            "authority_balance": self.authority_balance,
+            "deploy_slot": self.deploy_slot,
+            "code_hash": self.code_hash,
+            "buffers": self.buffers.iter().map(|b| b.to_json()).collect::<Vec<_>>(),
+            "activity": self.activity.as_ref().map(|a| a.to_json()),
+            "proposals": self.proposals.as_ref().map(|ps| ps.iter().map(|p| p.to_json()).collect::<Vec<_>>()),
+            "security_txt": self.security_txt.as_ref().map(|t| t.to_json()),
+            "security_txt_hash": self.security_txt_hash,
+        })
+    }
+
+    pub fn from_json(json: &serde_json::Value) -> Option<Self> {
+        let code_hash = json["code_hash"].as_str().map(|h| h.to_string());
+        Some(Self {
+            is_upgradeable: json["is_upgradeable"].as_bool()?,
+            authority: json["authority"].as_str().map(|a| a.to_string()),
+            authority_balance: json["authority_balance"].as_f64(),
+            deploy_slot: json["deploy_slot"].as_u64(),
+            // State saved before this field existed read security.txt from `code_hash`
+            security_txt_hash: match json.get("security_txt_hash") {
+                Some(hash) => hash.as_str().map(|h| h.to_string()),
+                None => code_hash.clone(),
+            },
+            code_hash,
+            buffers: json["buffers"].as_array().into_iter().flatten().filter_map(Buffer::from_json).collect(),
+            activity: AuthorityActivity::from_json(&json["activity"]),
+            proposals: json["proposals"].as_array()
+                .map(|ps| ps.iter().filter_map(Proposal::from_json).collect()),
+            security_txt: SecurityTxt::from_json(&json["security_txt"]),
+        })
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct Alert {
+    /// Fingerprint of subject + kind + state: the same condition always gets the same id
+    pub id: String,
     pub timestamp: String,
     pub severity: Severity,
+    /// Program id (or wallet address) the alert is about
+    pub subject: String,
     pub program: String,
+    /// Machine-readable alert type, e.g. "authority_changed"
+    pub kind: String,
     pub message: String,
 }
 
@@ -47,130 +131,557 @@ impl std::fmt::Display for Severity {
     }
 }
 
+impl Severity {
+    /// Lower-case name used in config files and JSON
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Critical => "critical",
+            Severity::High => "high",
+            Severity::Medium => "medium",
+            Severity::Info => "info",
+        }
+    }
+
+    /// Higher is more urgent
+    pub fn rank(&self) -> u8 {
+        match self {
+            Severity::Critical => 3,
+            Severity::High => 2,
+            Severity::Medium => 1,
+            Severity::Info => 0,
+        }
+    }
+}
+
+impl std::str::FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "critical" => Ok(Severity::Critical),
+            "high" => Ok(Severity::High),
+            "medium" => Ok(Severity::Medium),
+            "info" => Ok(Severity::Info),
+            _ => Err(format!("unknown severity '{}' (critical, high, medium, info)", s)),
+        }
+    }
+}
+
+/// Short stable id for an alert condition
+pub fn fingerprint(subject: &str, kind: &str, state: &str) -> String {
+    hex(&sha256(format!("{}\0{}\0{}", subject, kind, state).as_bytes()))[..12].to_string()
+}
+
+impl Alert {
+    /// `state` is the value that makes this occurrence distinct (the new authority, the new
+    /// hash, ...); alerts that differ only in wording share an id
+    pub fn new(severity: Severity, subject: &str, program: &str, kind: &str, state: &str, message: String) -> Self {
+        Self {
+            id: fingerprint(subject, kind, state),
+            timestamp: chrono::Local::now().format("%H:%M:%S").to_string(),
+            severity,
+            subject: subject.to_string(),
+            program: program.to_string(),
+            kind: kind.to_string(),
+            message,
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.id,
+            "time": self.timestamp,
+            "severity": format!("{:?}", self.severity),
+            "subject": self.subject,
+            "program": self.program,
+            "kind": self.kind,
+            "message": self.message,
+        })
+    }
+
+    pub fn from_json(json: &serde_json::Value) -> Option<Self> {
+        let text = |key: &str| json[key].as_str().unwrap_or_default().to_string();
+        Some(Self {
+            id: text("id"),
+            timestamp: json["time"].as_str()?.to_string(),
+            severity: json["severity"].as_str()?.parse().ok()?,
+            subject: text("subject"),
+            program: json["program"].as_str()?.to_string(),
+            kind: text("kind"),
+            message: json["message"].as_str()?.to_string(),
+        })
+    }
+}
+
 impl Daemon {
-    pub fn new(rpc_url: String) -> Self {
+    pub fn new(rpc_url: String, programs: Vec<WatchedProgram>, interval_secs: u64, cooldown_secs: u64, rules: RuleSet) -> Self {
         Self {
+            auditor: ContractAudit::new(rpc_url.clone(), rules),
             rpc_url,
+            programs,
+            interval_secs,
+            next_check: HashMap::new(),
             last_state: HashMap::new(),
+            restored: HashSet::new(),
             alerts: Vec::new(),
+            rpc_errors: 0,
+            risk_scores: HashMap::new(),
+            incidents: IncidentTracker::new(Vec::new(), cooldown_secs),
         }
     }
 
+    /// Start from persisted state: the first cycle then reports what changed while offline
+    pub fn restore(&mut self, saved: SavedState) {
+        self.restored = saved.programs.keys().cloned().collect();
+        self.last_state = saved.programs;
+        self.incidents.restore(saved.incidents);
+    }
+
+    pub fn state(&self) -> &HashMap<String, ProgramState> {
+        &self.last_state
+    }
+
+    pub fn programs(&self) -> &[WatchedProgram] {
+        &self.programs
+    }
+
+    /// Swap the watchlist; known state and schedules carry over, new programs are checked next cycle
+    pub fn set_programs(&mut self, programs: Vec<WatchedProgram>) {
+        self.programs = programs;
+    }
+
+    /// How often the main loop needs to wake up
+    pub fn tick_secs(&self) -> u64 {
+        self.programs.iter()
+            .filter_map(|p| p.interval_secs)
+            .fold(self.interval_secs, u64::min)
+            .max(1)
+    }
+
+    fn is_due(&self, program_id: &str) -> bool {
+        self.next_check.get(program_id).is_none_or(|t| *t <= std::time::Instant::now())
+    }
+
+    pub fn has_due(&self) -> bool {
+        self.programs.iter().any(|p| self.is_due(&p.id))
+    }
+
     pub async fn run_cycle(&mut self) -> Vec<Alert> {
-        let auditor = ContractAudit::new(self.rpc_url.clone());
         let authority_mapper = crate::authority::AuthorityMapper::new(self.rpc_url.clone());
         let mut new_alerts = Vec::new();
-        let now = chrono::Local::now().format("%H:%M:%S").to_string();
+        let mut checked = HashSet::new();
+        let client = reqwest::Client::new();
 
-        for (program_id, name) in PROGRAMS {
-            // Audit current state
-            let audit = match auditor.audit(program_id).await {
-                Ok(a) => a,
-                Err(_) => continue,
+        let due: Vec<WatchedProgram> = self.programs.iter().filter(|p| self.is_due(&p.id)).cloned().collect();
+        for program in &due {
+            let (program_id, name) = (program.id.as_str(), program.name.as_str());
+            let interval = program.interval_secs.unwrap_or(self.interval_secs);
+            self.next_check.insert(program.id.clone(), std::time::Instant::now() + std::time::Duration::from_secs(interval));
+
+            // One read of the program and its code serves both the audit and the authority map
+            let loaded = match load_program(&client, &self.rpc_url, program_id).await {
+                Ok(p) => p,
+                Err(_) => {
+                    self.rpc_errors += 1;
+                    continue;
+                }
             };
-            
-            let auth_info = authority_mapper.map_authority(program_id, name).await.ok();
-            
-            let current = ProgramState {
+            let audit = self.auditor.audit_program(program_id, &loaded).await;
+            self.risk_scores.insert(program.id.clone(), audit.risk_score);
+            let info = authority_mapper.map_program(program_id, name, &loaded, audit.control.clone()).await;
+
+            let prev = self.last_state.get(program_id);
+            let mut current = ProgramState {
                 is_upgradeable: audit.is_upgradeable,
-                authority: auth_info.as_ref().and_then(|a| a.upgrade_authority.clone()),
-                authority_balance: auth_info.as_ref().and_then(|a| a.authority_sol_balance).unwrap_or(0.0),
-                data_size: audit.data_size,
+                authority: info.upgrade_authority.clone(),
+                authority_balance: info.authority_sol_balance,
+                deploy_slot: info.deploy_slot,
+                code_hash: info.code_hash.clone(),
+                buffers: Vec::new(),
+                activity: None,
+                proposals: None,
+                security_txt: None,
+                security_txt_hash: None,
             };
+            // Unreadable code keeps the last known security.txt rather than reading as removed;
+            // it is compared once the code it came with can be read
+            match &audit.elf {
+                Some(elf) => {
+                    current.security_txt = elf.security_txt.clone();
+                    current.security_txt_hash = current.code_hash.clone();
+                }
+                None => {
+                    current.security_txt = prev.and_then(|p| p.security_txt.clone());
+                    current.security_txt_hash = prev.and_then(|p| p.security_txt_hash.clone());
+                }
+            }
+            let mut authority_actions = Vec::new();
+            // New code is staged in a buffer before an upgrade, so this is the early warning
+            if let Some(authority) = current.authority.clone() {
+                match find_buffers(&self.rpc_url, &authority).await {
+                    Ok(buffers) => current.buffers = buffers,
+                    Err(_) => {
+                        self.rpc_errors += 1;
+                        current.buffers = prev.map(|p| p.buffers.clone()).unwrap_or_default();
+                    }
+                }
+                match poll_activity(&client, prev.and_then(|p| p.activity.as_ref()), &authority).await {
+                    Ok((activity, actions)) => {
+                        current.activity = Some(activity);
+                        authority_actions = actions;
+                    }
+                    Err(_) => {
+                        self.rpc_errors += 1;
+                        current.activity = prev.and_then(|p| p.activity.clone());
+                    }
+                }
+                // A multisig or DAO announces upgrades as proposals, ahead of any timelock
+                if let Some(controller) = info.control.as_ref().and_then(|c| c.account.as_ref()) {
+                    let target = Target { program_id, programdata: info.programdata_account.as_deref() };
+                    let known = prev.and_then(|p| p.proposals.as_deref()).unwrap_or(&[]);
+                    match find_proposals(&self.rpc_url, controller, &target, known).await {
+                        Ok(proposals) => current.proposals = Some(proposals),
+                        Err(_) => {
+                            self.rpc_errors += 1;
+                            current.proposals = prev.and_then(|p| p.proposals.clone());
+                        }
+                    }
+                }
+            }
+            checked.insert(program.id.clone());
 
             // Compare with last known state
-            if let Some(prev) = self.last_state.get(*program_id) {
+            let first_alert = new_alerts.len();
+            let alert = |severity: Severity, kind: &str, state: &str, message: String| {
+                Alert::new(severity, program_id, name, kind, state, message)
+            };
+            if let Some(prev) = prev {
                 // CRITICAL: Authority changed
                 if prev.authority != current.authority {
-                    let alert = Alert {
-                        timestamp: now.clone(),
-                        severity: Severity::Critical,
-                        program: name.to_string(),
-                        message: format!(
+                    new_alerts.push(alert(
+                        Severity::Critical,
+                        "authority_changed",
+                        current.authority.as_deref().unwrap_or("none"),
+                        format!(
                             "AUTHORITY CHANGED! {} → {}",
                             prev.authority.as_deref().unwrap_or("none"),
                             current.authority.as_deref().unwrap_or("none")
                         ),
-                    };
-                    new_alerts.push(alert);
-                }
-
-                // HIGH: Program was upgraded (data size changed)
-                if prev.data_size != current.data_size && prev.data_size > 0 {
-                    let alert = Alert {
-                        timestamp: now.clone(),
-                        severity: Severity::High,
-                        program: name.to_string(),
-                        message: format!(
-                            "PROGRAM UPGRADED! Size {} → {} bytes",
-                            prev.data_size, current.data_size
-                        ),
-                    };
-                    new_alerts.push(alert);
-                }
-
-                // MEDIUM: Authority balance changed significantly (>10 SOL movement)
-                let bal_diff = (current.authority_balance - prev.authority_balance).abs();
-                if bal_diff > 10.0 {
-                    let alert = Alert {
-                        timestamp: now.clone(),
-                        severity: Severity::Medium,
-                        program: name.to_string(),
-                        message: format!(
-                            "Authority balance shift: {:.2} → {:.2} SOL (Δ{:.2})",
-                            prev.authority_balance, current.authority_balance, bal_diff
-                        ),
-                    };
-                    new_alerts.push(alert);
+                    ));
+                }
+
+                // HIGH: Program was upgraded (new deploy slot or different bytecode)
+                if let (Some(prev_slot), Some(slot), Some(prev_hash), Some(hash)) =
+                    (prev.deploy_slot, current.deploy_slot, &prev.code_hash, &current.code_hash)
+                {
+                    if prev_slot != slot || prev_hash != hash {
+                        new_alerts.push(alert(
+                            Severity::High,
+                            "upgraded",
+                            &format!("{}:{}", slot, hash),
+                            format!(
+                                "PROGRAM UPGRADED! slot {} → {} | hash {} → {}{}",
+                                prev_slot, slot, prev_hash, hash,
+                                if prev_hash == hash { " (same bytecode redeployed)" } else { "" }
+                            ),
+                        ));
+                    }
+                }
+
+                // MEDIUM: an upgrade dropped or rewrote the published security contacts. Compared
+                // per code version, so an upgrade whose code couldn't be read right away isn't missed.
+                if let (Some(prev_hash), Some(hash)) = (&prev.security_txt_hash, &current.security_txt_hash) {
+                    if prev_hash != hash {
+                        match (&prev.security_txt, &current.security_txt) {
+                            (Some(before), None) => new_alerts.push(alert(
+                                Severity::Medium,
+                                "security_txt_removed",
+                                hash,
+                                format!("Upgrade REMOVED the security.txt (was {})", before.summary()),
+                            )),
+                            (Some(before), Some(after)) if before != after => new_alerts.push(alert(
+                                Severity::Medium,
+                                "security_txt_changed",
+                                hash,
+                                format!("Upgrade changed the security.txt: {}", after.changes(before).join("; ")),
+                            )),
+                            (None, Some(after)) => new_alerts.push(alert(
+                                Severity::Info,
+                                "security_txt_added",
+                                hash,
+                                format!("Upgrade added a security.txt: {}", after.summary()),
+                            )),
+                            _ => {}
+                        }
+                    }
+                }
+
+                // MEDIUM: Authority balance changed significantly (>10 SOL movement);
+                // an unreadable balance on either side isn't a movement
+                if let (Some(before), Some(after)) = (prev.authority_balance, current.authority_balance) {
+                    let bal_diff = (after - before).abs();
+                    if bal_diff > 10.0 {
+                        new_alerts.push(alert(
+                            Severity::Medium,
+                            "authority_balance_shift",
+                            "",
+                            format!("Authority balance shift: {:.2} → {:.2} SOL (Δ{:.2})", before, after, bal_diff),
+                        ));
+                    }
+                }
+
+                // HIGH: New deploy buffer from the authority; MEDIUM: an existing one got more code
+                for buffer in &current.buffers {
+                    let summary = format!(
+                        "{} | {}/{} bytes | hash {} ({})",
+                        buffer.address, buffer.written, buffer.capacity,
+                        &buffer.code_hash[..16], buffer.compare(current.code_hash.as_deref())
+                    );
+                    match prev.buffers.iter().find(|b| b.address == buffer.address) {
+                        None => new_alerts.push(alert(
+                            Severity::High,
+                            "deploy_buffer",
+                            &buffer.address,
+                            format!("NEW DEPLOY BUFFER — upgrade may be imminent: {}", summary),
+                        )),
+                        Some(old) if old.written != buffer.written || old.code_hash != buffer.code_hash => new_alerts.push(alert(
+                            Severity::Medium,
+                            "deploy_buffer_grew",
+                            &format!("{}:{}", buffer.address, buffer.code_hash),
+                            format!("Deploy buffer written {} → {} bytes: {}", old.written, buffer.written, summary),
+                        )),
+                        Some(_) => {}
+                    }
                 }
 
                 // HIGH: Previously immutable program became upgradeable (should be impossible but check)
                 if !prev.is_upgradeable && current.is_upgradeable {
-                    let alert = Alert {
-                        timestamp: now.clone(),
-                        severity: Severity::Critical,
-                        program: name.to_string(),
-                        message: "IMMUTABLE PROGRAM BECAME UPGRADEABLE — POSSIBLE ATTACK".to_string(),
-                    };
-                    new_alerts.push(alert);
+                    new_alerts.push(alert(
+                        Severity::Critical,
+                        "became_upgradeable",
+                        "",
+                        "IMMUTABLE PROGRAM BECAME UPGRADEABLE — POSSIBLE ATTACK".to_string(),
+                    ));
                 }
             } else {
                 // First scan — just record baseline
-                new_alerts.push(Alert {
-                    timestamp: now.clone(),
-                    severity: Severity::Info,
-                    program: name.to_string(),
-                    message: format!(
-                        "Baseline: {} | auth: {}",
-                        if current.is_upgradeable { "upgradeable" } else { "immutable" },
-                        current.authority.as_deref().unwrap_or("none")
+                new_alerts.push(alert(
+                    Severity::Info,
+                    "baseline",
+                    "",
+                    format!(
+                        "Baseline{}: {} | auth: {}{}{}{}",
+                        program.category.as_ref().map(|c| format!(" [{}]", c)).unwrap_or_default(),
+                        audit.program.mutability(),
+                        current.authority.as_deref().unwrap_or("none"),
+                        info.control.as_ref()
+                            .map(|c| format!(" ({})", c.label()))
+                            .unwrap_or_default(),
+                        match (current.deploy_slot, &current.code_hash) {
+                            (Some(slot), Some(hash)) => format!(" | deployed slot {} | hash {}", slot, &hash[..16]),
+                            _ => String::new(),
+                        },
+                        match current.buffers.len() {
+                            0 => String::new(),
+                            n => format!(" | {} pending deploy buffer(s)", n),
+                        }
+                    ),
+                ));
+            }
+
+            // HIGH: upgrade proposal created; CRITICAL: approved or executed. Proposals that
+            // were already finished the first time we looked are not news.
+            let known = prev.and_then(|p| p.proposals.as_ref());
+            for proposal in current.proposals.iter().flatten() {
+                let before = known.and_then(|ps| ps.iter().find(|p| p.address == proposal.address)).map(|p| p.stage);
+                let (severity, kind, headline) = match (before, proposal.stage) {
+                    (Some(b), s) if b == s => continue,
+                    (None, Stage::Pending) => (Severity::High, "proposal_created", "UPGRADE PROPOSAL CREATED"),
+                    (_, Stage::Approved) => (Severity::Critical, "proposal_approved", "UPGRADE PROPOSAL APPROVED"),
+                    (_, Stage::Executed) if known.is_some() => (Severity::Critical, "proposal_executed", "UPGRADE PROPOSAL EXECUTED"),
+                    (Some(_), Stage::Closed) => (Severity::Info, "proposal_closed", "Upgrade proposal closed"),
+                    _ => continue,
+                };
+                new_alerts.push(alert(
+                    severity,
+                    kind,
+                    &format!("{}:{}", proposal.address, proposal.stage.name()),
+                    format!("{}: {}", headline, proposal.describe()),
+                ));
+            }
+
+            // Outgoing transactions from the authority wallet, decoded
+            for action in authority_actions {
+                new_alerts.push(alert(action.severity, action.kind, &action.state, action.message));
+            }
+
+            // MEDIUM: the authority key hasn't been used in a long time (lost key, or cold storage
+            // nobody has checked). Fires every cycle while true, resolves when the wallet acts.
+            if let Some(activity) = &current.activity {
+                let days = program.dormant_days.unwrap_or(DORMANT_DAYS);
+                let idle = activity.idle_secs(chrono::Utc::now().timestamp());
+                if idle.is_none_or(|secs| secs >= days as i64 * 86_400) {
+                    new_alerts.push(alert(
+                        Severity::Medium,
+                        "dormant_authority",
+                        &activity.authority,
+                        match idle {
+                            Some(secs) => format!("Dormant authority {}: no transactions for {} days", activity.authority, secs / 86_400),
+                            None => format!("Dormant authority {}: no transactions ever", activity.authority),
+                        },
+                    ));
+                }
+            }
+
+            // CRITICAL/HIGH: deployed state differs from what the watchlist expects.
+            // These fire every cycle while true; the incident tracker dedupes and resolves them.
+            if !program.authority_ok(current.authority.as_deref()) {
+                new_alerts.push(alert(
+                    Severity::Critical,
+                    "unexpected_authority",
+                    current.authority.as_deref().unwrap_or("none"),
+                    format!(
+                        "UNEXPECTED AUTHORITY: {} (expected {})",
+                        current.authority.as_deref().unwrap_or("none"),
+                        program.expected_authority.as_deref().unwrap_or("none")
+                    ),
+                ));
+            }
+            if !program.hash_ok(current.code_hash.as_deref()) {
+                new_alerts.push(alert(
+                    Severity::High,
+                    "unexpected_code",
+                    current.code_hash.as_deref().unwrap_or("unknown"),
+                    format!(
+                        "UNEXPECTED CODE: hash {} (expected {})",
+                        current.code_hash.as_deref().unwrap_or("unknown"),
+                        program.expected_hash.as_deref().unwrap_or("none")
                     ),
-                });
+                ));
             }
 
+            if self.restored.remove(program_id) {
+                for alert in &mut new_alerts[first_alert..] {
+                    alert.message = format!("{} (while offline)", alert.message);
+                }
+            }
             self.last_state.insert(program_id.to_string(), current);
 
             // Rate limit
             tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
         }
 
-        self.alerts.extend(new_alerts.clone());
-        new_alerts
+        let notify = self.incidents.process(new_alerts, &checked, chrono::Utc::now().timestamp());
+        self.alerts.extend(notify.clone());
+        notify
     }
 }
 
-pub async fn run_daemon(rpc_url: &str, interval_secs: u64, json_output: bool) {
-    let mut daemon = Daemon::new(rpc_url.to_string());
+/// How `run_daemon` runs, from the command line
+pub struct GuardianOptions {
+    pub interval_secs: u64,
+    /// Minimum gap between repeat notifications of the same open alert
+    pub cooldown_secs: u64,
+    pub json_output: bool,
+    /// Address for the HTTP API, if enabled
+    pub listen: Option<String>,
+    /// Audit rules behind the per-program risk scores
+    pub rules: RuleSet,
+}
+
+pub async fn run_daemon(
+    rpc_url: &str,
+    source: Source,
+    programs: Vec<WatchedProgram>,
+    router: &AlertRouter,
+    options: GuardianOptions,
+) {
+    let GuardianOptions { interval_secs, cooldown_secs, json_output, listen, rules } = options;
+    let mut daemon = Daemon::new(rpc_url.to_string(), programs, interval_secs, cooldown_secs, rules);
     let mut cycle = 0u64;
 
+    let store = match GuardianStore::open() {
+        Ok(store) => Some(store),
+        Err(e) => {
+            eprintln!("⚠️  Guardian state will not be persisted: {}", e);
+            None
+        }
+    };
+    let saved = store.as_ref().and_then(|s| s.load_state().unwrap_or_else(|e| {
+        eprintln!("⚠️  Ignoring saved guardian state: {}", e);
+        None
+    }));
+    let restored_from = saved.as_ref().map(|s| (s.programs.len(), s.saved_at));
+    if let Some(saved) = saved {
+        daemon.restore(saved);
+    }
+
+    let status: SharedStatus = Arc::new(Mutex::new(StatusBoard {
+        started_at: chrono::Utc::now().timestamp(),
+        programs: daemon.programs().to_vec(),
+        states: daemon.state().clone(),
+        ..Default::default()
+    }));
+    if let Some(addr) = listen.clone() {
+        // Seed /alerts with what happened before this run
+        if let Some(history) = store.as_ref().and_then(|s| s.alerts().ok()) {
+            let mut board = status.lock().unwrap();
+            for stored in history.into_iter().rev().take(crate::api::RECENT_ALERTS).rev() {
+                board.recent.push_back((stored.recorded_at, stored.alert));
+            }
+        }
+        let server_status = status.clone();
+        tokio::spawn(async move {
+            if let Err(e) = crate::api::serve(&addr, server_status).await {
+                eprintln!("⚠️  HTTP API stopped: {}", e);
+            }
+        });
+    }
+
     if !json_output {
         println!("🔮 Solana DeFi Guardian — Autonomous Monitor");
-        println!("    Tracking {} protocols every {}s", PROGRAMS.len(), interval_secs);
-        println!("    Watching for: authority changes, upgrades, balance anomalies");
-        println!("    Press Ctrl+C to stop\n");
+        println!("    Tracking {} programs from {} every {}s", daemon.programs().len(), source, interval_secs);
+        println!("    Watching for: authority changes and activity, deploy buffers, upgrade proposals, upgrades, balance anomalies");
+        println!("    Open alerts repeat every {}s until resolved or acked (solscan guardian ack <id>)", cooldown_secs);
+        for route in &router.routes {
+            println!("    Alerting {} (≥ {})", route.sink.name(), route.min_severity.name());
+        }
+        if let Some(addr) = &listen {
+            println!("    Serving /metrics, /status, /alerts on http://{}", addr);
+        }
+        if let Some((programs, saved_at)) = restored_from {
+            let when = chrono::DateTime::from_timestamp(saved_at, 0)
+                .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default();
+            println!("    Restored {} programs from {} — changes since then will be reported", programs, when);
+        }
+        println!("    Press Ctrl+C to stop (SIGHUP reloads the program list)\n");
     }
 
+    #[cfg(unix)]
+    let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()).ok();
+
     loop {
+        if !daemon.has_due() {
+            #[cfg(unix)]
+            if let Some(hangup) = hangup.as_mut() {
+                let sleep = tokio::time::sleep(tokio::time::Duration::from_secs(daemon.tick_secs()));
+                tokio::select! {
+                    _ = sleep => {}
+                    _ = hangup.recv() => match source.load() {
+                        Ok(programs) => {
+                            eprintln!("🔄 Reloaded {}: {} programs", source, programs.len());
+                            status.lock().unwrap().programs = programs.clone();
+                            daemon.set_programs(programs);
+                        }
+                        Err(e) => eprintln!("⚠️  Reload failed, keeping current program list: {}", e),
+                    },
+                }
+                continue;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_secs(daemon.tick_secs())).await;
+            continue;
+        }
         cycle += 1;
         let now = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
         
@@ -178,21 +689,38 @@ pub async fn run_daemon(rpc_url: &str, interval_secs: u64, json_output: bool) {
             eprint!("[{}] Cycle {}... ", now, cycle);
         }
 
+        if let Some(acks) = store.as_ref().and_then(|s| s.acks().ok()) {
+            daemon.incidents.apply_acks(&acks);
+        }
+        let started = std::time::Instant::now();
         let alerts = daemon.run_cycle().await;
+        {
+            let mut board = status.lock().unwrap();
+            let finished_at = chrono::Utc::now().timestamp();
+            board.cycles = cycle;
+            board.last_cycle_at = finished_at;
+            board.last_cycle_secs = started.elapsed().as_secs_f64();
+            board.rpc_errors = daemon.rpc_errors;
+            board.states = daemon.state().clone();
+            board.risk_scores = daemon.risk_scores.clone();
+            board.record_alerts(&alerts, finished_at);
+        }
+        if let Some(store) = &store {
+            if let Err(e) = store.save_state(daemon.state(), daemon.incidents.incidents()).and_then(|_| store.append_alerts(&alerts)) {
+                eprintln!("⚠️  Could not persist guardian state: {}", e);
+            }
+        }
+        router.dispatch(&crate::incidents::group(&alerts)).await;
         
-        let critical = alerts.iter().filter(|a| matches!(a.severity, Severity::Critical | Severity::High)).count();
         let info = alerts.iter().filter(|a| matches!(a.severity, Severity::Info)).count();
+        let critical = alerts.len() - info;
 
         if json_output {
             for alert in &alerts {
                 if !matches!(alert.severity, Severity::Info) || cycle == 1 {
-                    println!("{}", serde_json::json!({
-                        "cycle": cycle,
-                        "time": alert.timestamp,
-                        "severity": format!("{:?}", alert.severity),
-                        "program": alert.program,
-                        "message": alert.message,
-                    }));
+                    let mut json = alert.to_json();
+                    json["cycle"] = serde_json::json!(cycle);
+                    println!("{}", json);
                 }
             }
         } else {
@@ -200,16 +728,17 @@ pub async fn run_daemon(rpc_url: &str, interval_secs: u64, json_output: bool) {
                 eprintln!("⚠️  {} ALERTS!", critical);
                 for alert in &alerts {
                     if !matches!(alert.severity, Severity::Info) {
-                        println!("  {} [{}] {}", alert.severity, alert.program, alert.message);
+                        println!("  {} [{}] {} ({})", alert.severity, alert.program, alert.message, alert.id);
                     }
                 }
+            } else if cycle == 1 && restored_from.is_some() {
+                eprintln!("no changes while offline ✅{}",
+                    if info > 0 { format!(" ({} new programs baselined)", info) } else { String::new() });
             } else if cycle == 1 {
                 eprintln!("{} programs baselined ✅", info);
             } else {
                 eprintln!("all clear ✅");
             }
         }
-
-        tokio::time::sleep(tokio::time::Duration::from_secs(interval_secs)).await;
     }
 }
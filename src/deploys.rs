@@ -0,0 +1,171 @@
+use crate::authority::{code_hash, without_padding, Loader, Program, UPGRADEABLE_LOADER};
+use crate::buffers::BUFFER_HEADER_LEN;
+use crate::governance::get_account;
+use serde_json::Value;
+
+/// One loader instruction in a program's life
+#[derive(Clone, Debug)]
+pub struct DeployEvent {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// deploy, upgrade, set_authority or close
+    pub kind: &'static str,
+    /// Authority that signed the instruction
+    pub signer: Option<String>,
+    /// Authority after a SetAuthority; None when it was revoked
+    pub new_authority: Option<String>,
+    /// Buffer the code was deployed from
+    pub buffer: Option<String>,
+    /// The deployed ELF, when it's still on chain: the current code, or a buffer left open
+    pub elf_len: Option<usize>,
+    pub elf_hash: Option<String>,
+}
+
+impl DeployEvent {
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({
+            "signature": self.signature,
+            "slot": self.slot,
+            "block_time": self.block_time,
+            "kind": self.kind,
+            "signer": self.signer,
+            "new_authority": self.new_authority,
+            "buffer": self.buffer,
+            "elf_len": self.elf_len,
+            "elf_hash": self.elf_hash,
+        })
+    }
+
+    fn ships_code(&self) -> bool {
+        self.kind == "deploy" || self.kind == "upgrade"
+    }
+}
+
+/// Deploys, upgrades, authority changes and closes of `program_id`, oldest first, from the
+/// last `limit` transactions that touched its ProgramData account. `program` is `program_id` as
+/// decoded by `load_program`.
+pub async fn history(client: &reqwest::Client, rpc_url: &str, program_id: &str, program: &Program, limit: usize) -> Result<Vec<DeployEvent>, Box<dyn std::error::Error>> {
+    if program.loader != Some(Loader::Upgradeable) {
+        return Err(format!("{} is not an upgradeable program (owner {})", program_id, program.owner).into());
+    }
+    let programdata = program.programdata.clone().ok_or("Program account has no ProgramData address")?;
+    // The current code is the only ELF still on chain; old buffers are closed on deploy
+    let current = match (program.deploy_slot, program.elf.as_deref()) {
+        (Some(slot), Some(elf)) => Some((slot, without_padding(elf).len(), code_hash(without_padding(elf)))),
+        _ => None,
+    };
+
+    let mut events = Vec::new();
+    for sig in crate::get_signature_history(client, &programdata, limit, None).await? {
+        if sig.err {
+            continue;
+        }
+        let Some(tx) = crate::get_transaction(client, &sig.signature).await? else { continue };
+        for ix in crate::parsed_instructions(&tx) {
+            if ix["program"].as_str() != Some("bpf-upgradeable-loader") {
+                continue;
+            }
+            let info = &ix["parsed"]["info"];
+            let text = |key: &str| info[key].as_str().map(|v| v.to_string());
+            let (kind, target) = match ix["parsed"]["type"].as_str().unwrap_or("") {
+                "deployWithMaxDataLen" => ("deploy", text("programAccount")),
+                "upgrade" => ("upgrade", text("programAccount")),
+                "setAuthority" | "setAuthorityChecked" => ("set_authority", text("account")),
+                "close" => ("close", text("account")),
+                _ => continue,
+            };
+            if target.as_deref() != Some(program_id) && target.as_deref() != Some(programdata.as_str()) {
+                continue;
+            }
+            let mut event = DeployEvent {
+                signature: sig.signature.clone(),
+                slot: sig.slot,
+                block_time: sig.block_time,
+                kind,
+                signer: text("authority"),
+                new_authority: if kind == "set_authority" { text("newAuthority") } else { None },
+                buffer: text("bufferAccount"),
+                elf_len: None,
+                elf_hash: None,
+            };
+            let recovered = match (&current, &event.buffer) {
+                (Some((slot, len, hash)), _) if event.ships_code() && event.slot == *slot => Some((*len, hash.clone())),
+                (_, Some(buffer)) => buffer_elf(client, rpc_url, buffer).await,
+                _ => None,
+            };
+            if let Some((len, hash)) = recovered {
+                event.elf_len = Some(len);
+                event.elf_hash = Some(hash);
+            }
+            events.push(event);
+        }
+    }
+    events.reverse();
+    Ok(events)
+}
+
+/// Size and hash of the code in a buffer that outlived its deploy; Deploy and Upgrade normally close it
+async fn buffer_elf(client: &reqwest::Client, rpc_url: &str, buffer: &str) -> Option<(usize, String)> {
+    let (owner, data) = get_account(client, rpc_url, buffer).await.ok()??;
+    // Buffer accounts start with enum tag 1
+    if owner != UPGRADEABLE_LOADER || data.first() != Some(&1) {
+        return None;
+    }
+    let elf = without_padding(data.get(BUFFER_HEADER_LEN..)?);
+    (!elf.is_empty()).then(|| (elf.len(), code_hash(elf)))
+}
+
+fn date(ts: Option<i64>) -> String {
+    ts.and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "unknown time    ".to_string())
+}
+
+pub fn print_history(events: &[DeployEvent]) {
+    println!("\n  📜 Deployment history:");
+    if events.is_empty() {
+        println!("    no loader transactions found");
+        return;
+    }
+    for e in events {
+        let label = match e.kind {
+            "deploy" => "🚀 DEPLOY",
+            "upgrade" => "⬆️  UPGRADE",
+            "set_authority" => "🔑 SET AUTHORITY",
+            _ => "🗑️  CLOSE",
+        };
+        println!("    {}  slot {:<10} {:16} by {}", date(e.block_time), e.slot, label, e.signer.as_deref().unwrap_or("?"));
+        if e.kind == "set_authority" {
+            println!("      → new authority {}", e.new_authority.as_deref().unwrap_or("none (immutable)"));
+        }
+        match (&e.elf_hash, e.elf_len, &e.buffer) {
+            (Some(hash), Some(len), _) => println!("      code {} ({} bytes)", &hash[..16], len),
+            (None, _, Some(buffer)) => println!("      from buffer {} (code no longer on chain)", buffer),
+            _ => {}
+        }
+        println!("      tx {}", e.signature);
+    }
+
+    let shipped: Vec<&DeployEvent> = events.iter().filter(|e| e.ships_code()).collect();
+    let times: Vec<i64> = shipped.iter().filter_map(|e| e.block_time).collect();
+    println!("\n    {} code releases", shipped.len());
+    if let (Some(first), Some(last)) = (times.first(), times.last()) {
+        if times.len() > 1 {
+            let days = (last - first) as f64 / 86_400.0;
+            println!("    one every {:.0} days on average over {:.0} days", days / (times.len() - 1) as f64, days);
+        }
+    }
+    let mut signers: Vec<(&str, usize)> = Vec::new();
+    for e in &shipped {
+        let signer = e.signer.as_deref().unwrap_or("?");
+        match signers.iter_mut().find(|(s, _)| *s == signer) {
+            Some((_, n)) => *n += 1,
+            None => signers.push((signer, 1)),
+        }
+    }
+    signers.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+    for (signer, n) in signers {
+        println!("    signed by {} ×{}", signer, n);
+    }
+}
@@ -0,0 +1,122 @@
+/// Minimal base64 / base58 codecs — keeps us free of extra dependencies
+const BASE64_TABLE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BS58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+pub fn base64_decode(input: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    // Simple base64 decoder
+    let mut out = Vec::new();
+    let mut buf: u32 = 0;
+    let mut bits: u32 = 0;
+    for &c in input.as_bytes() {
+        if c == b'=' || c == b'\n' || c == b'\r' { continue; }
+        let val = BASE64_TABLE.iter().position(|&t| t == c).ok_or("invalid base64")? as u32;
+        buf = (buf << 6) | val;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push(((buf >> bits) & 0xFF) as u8);
+        }
+    }
+    Ok(out)
+}
+
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+        out.push(BASE64_TABLE[(n >> 18) as usize & 63] as char);
+        out.push(BASE64_TABLE[(n >> 12) as usize & 63] as char);
+        out.push(if chunk.len() > 1 { BASE64_TABLE[(n >> 6) as usize & 63] as char } else { '=' });
+        out.push(if chunk.len() > 2 { BASE64_TABLE[n as usize & 63] as char } else { '=' });
+    }
+    out
+}
+
+pub fn bs58_encode(bytes: &[u8]) -> String {
+    if bytes.is_empty() { return String::new(); }
+
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in bytes {
+        let mut carry = byte as u32;
+        for d in digits.iter_mut() {
+            carry += (*d as u32) * 256;
+            *d = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    // Leading zeros
+    let mut result = String::new();
+    for &b in bytes {
+        if b == 0 { result.push('1'); } else { break; }
+    }
+
+    for &d in digits.iter().rev() {
+        result.push(BS58_ALPHABET[d as usize] as char);
+    }
+    result
+}
+
+pub fn bs58_decode(input: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in input.bytes() {
+        let mut carry = BS58_ALPHABET.iter().position(|&a| a == c).ok_or("invalid base58")? as u32;
+        for b in bytes.iter_mut() {
+            carry += (*b as u32) * 58;
+            *b = (carry & 0xFF) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push((carry & 0xFF) as u8);
+            carry >>= 8;
+        }
+    }
+
+    // Leading '1's are leading zero bytes
+    for c in input.bytes() {
+        if c == b'1' { bytes.push(0); } else { break; }
+    }
+    bytes.reverse();
+    Ok(bytes)
+}
+
+/// Decode a base58 address into a 32-byte public key
+pub fn pubkey_bytes(address: &str) -> Result<[u8; 32], Box<dyn std::error::Error>> {
+    let bytes = bs58_decode(address)?;
+    bytes.as_slice().try_into().map_err(|_| format!("not a 32-byte address: {}", address).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_rfc4648_vectors() {
+        for (plain, encoded) in [("", ""), ("f", "Zg=="), ("fo", "Zm8="), ("foo", "Zm9v"), ("foob", "Zm9vYg=="), ("fooba", "Zm9vYmE="), ("foobar", "Zm9vYmFy")] {
+            assert_eq!(base64_encode(plain.as_bytes()), encoded);
+            assert_eq!(base64_decode(encoded).unwrap(), plain.as_bytes());
+        }
+    }
+
+    #[test]
+    fn base58_vectors() {
+        for (bytes, encoded) in [
+            (&b""[..], ""),
+            (&[0][..], "1"),
+            (&[0, 0, 0x28, 0x7f, 0xb4, 0xcd][..], "11233QC4"),
+            (&b"Hello World!"[..], "2NEpo7TZRRrLZSi2U"),
+            (&[0; 32][..], "11111111111111111111111111111111"),
+        ] {
+            assert_eq!(bs58_encode(bytes), encoded);
+            assert_eq!(bs58_decode(encoded).unwrap(), bytes);
+        }
+        assert!(bs58_decode("0OIl").is_err());
+        assert_eq!(pubkey_bytes("11111111111111111111111111111111").unwrap(), [0; 32]);
+        assert!(pubkey_bytes("2NEpo7TZRRrLZSi2U").is_err());
+    }
+}
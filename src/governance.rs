@@ -0,0 +1,383 @@
+use crate::crypto::{find_program_address, is_on_curve, sha256};
+use crate::encoding::{base64_decode, bs58_encode, pubkey_bytes};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+pub const SQUADS_V3: &str = "SMPLecH534NA9acpos4G6x7uf3LWbCAwZQE9e8ZekMu";
+pub const SQUADS_V4: &str = "SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf";
+/// SPL Governance deployments: the shared Realms instance, then the Mango DAO's own
+pub const GOVERNANCE_PROGRAMS: &[&str] = &[
+    "GovER5Lthms3bLBqWub97yVrMPEeLk6mKrbDjrRg29NS",
+    "GqTPL6qRf5aUuqscLh8Rg2HTxPUXfhhAXDptTLhp1t2J",
+];
+/// All-zero pubkey, which Squads uses for "no config authority"
+const DEFAULT_PUBKEY: &str = "11111111111111111111111111111111";
+
+/// Vault / authority indexes tried when matching a PDA to a multisig
+const VAULT_INDEXES: u8 = 4;
+/// Transactions read when looking for the account behind a PDA authority
+const RESOLVE_TXS: usize = 10;
+
+/// Who can sign with an upgrade authority
+#[derive(Clone, Debug, PartialEq)]
+pub enum ControlKind {
+    /// An ordinary keypair: one person, one key
+    HotKey,
+    SquadsV3,
+    SquadsV4,
+    Governance,
+    /// A PDA of some program we couldn't identify
+    UnknownPda,
+}
+
+#[derive(Clone, Debug)]
+pub struct Control {
+    pub kind: ControlKind,
+    /// The multisig or governance account behind the authority
+    pub account: Option<String>,
+    pub threshold: Option<u32>,
+    pub members: Option<u32>,
+    /// Minimum delay between approval and execution
+    pub timelock_secs: Option<u64>,
+    /// Governance only: time proposals stay open for voting
+    pub voting_secs: Option<u64>,
+    /// Anything else worth knowing, e.g. vote thresholds or a config authority
+    pub details: Vec<String>,
+}
+
+fn duration(secs: u64) -> String {
+    match secs {
+        0 => "none".to_string(),
+        s if s % 86_400 == 0 => format!("{}d", s / 86_400),
+        s if s % 3_600 == 0 => format!("{}h", s / 3_600),
+        s => format!("{}m", s.div_ceil(60)),
+    }
+}
+
+impl Control {
+    fn new(kind: ControlKind) -> Self {
+        Self {
+            kind,
+            account: None,
+            threshold: None,
+            members: None,
+            timelock_secs: None,
+            voting_secs: None,
+            details: Vec::new(),
+        }
+    }
+
+    /// One line, e.g. "Squads v4 3-of-5, 48h timelock"
+    pub fn label(&self) -> String {
+        let base = match self.kind {
+            ControlKind::HotKey => return "1-of-1 hot key".to_string(),
+            ControlKind::UnknownPda => return "PDA of an unidentified program".to_string(),
+            ControlKind::SquadsV3 => "Squads v3",
+            ControlKind::SquadsV4 => "Squads v4",
+            ControlKind::Governance => "SPL Governance",
+        };
+        let mut parts = vec![match (self.threshold, self.members) {
+            (Some(t), Some(m)) => format!("{} {}-of-{}", base, t, m),
+            _ => base.to_string(),
+        }];
+        if let Some(voting) = self.voting_secs {
+            parts.push(format!("{} voting", duration(voting)));
+        }
+        if let Some(timelock) = self.timelock_secs {
+            parts.push(format!("{} timelock", duration(timelock)));
+        }
+        parts.extend(self.details.iter().cloned());
+        parts.join(", ")
+    }
+
+    /// Least time between someone deciding to upgrade and the upgrade landing
+    pub fn reaction_window_secs(&self) -> u64 {
+        self.voting_secs.unwrap_or(0) + self.timelock_secs.unwrap_or(0)
+    }
+
+    /// Risk points for an upgradeable program controlled this way (a hot key is the old flat 30)
+    pub fn upgrade_risk(&self) -> u8 {
+        let base: u8 = match self.kind {
+            ControlKind::HotKey => return 30,
+            ControlKind::UnknownPda => return 25,
+            ControlKind::Governance => 12,
+            ControlKind::SquadsV3 | ControlKind::SquadsV4 => match self.threshold.unwrap_or(1) {
+                0 | 1 => 28,
+                t => 20u8.saturating_sub(2 * t.min(5) as u8),
+            },
+        };
+        let window = self.reaction_window_secs();
+        let discount = if window >= 86_400 { 5 } else if window >= 3_600 { 2 } else { 0 };
+        base.saturating_sub(discount).max(5)
+    }
+
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({
+            "kind": format!("{:?}", self.kind),
+            "label": self.label(),
+            "account": self.account,
+            "threshold": self.threshold,
+            "members": self.members,
+            "timelock_secs": self.timelock_secs,
+            "voting_secs": self.voting_secs,
+            "details": self.details,
+        })
+    }
+}
+
+/// Owner and data of an account, None if it doesn't exist; RPC errors are errors
+pub async fn get_account(client: &reqwest::Client, rpc_url: &str, address: &str) -> Result<Option<(String, Vec<u8>)>, Box<dyn std::error::Error>> {
+    let body = serde_json::json!({
+        "jsonrpc": "2.0", "id": 1,
+        "method": "getAccountInfo",
+        "params": [address, { "encoding": "base64" }]
+    });
+    let resp: Value = client.post(rpc_url).json(&body).send().await?.json().await?;
+    // A rate-limited or failed call must not read as a missing account
+    if let Some(err) = resp.get("error") {
+        return Err(format!("RPC error: {}", err).into());
+    }
+    if resp["result"].is_null() {
+        return Err(format!("RPC returned no result for {}", address).into());
+    }
+    let value = &resp["result"]["value"];
+    if value.is_null() {
+        return Ok(None);
+    }
+    let data = value["data"].as_array().and_then(|d| d.first()).and_then(|v| v.as_str()).unwrap_or("");
+    Ok(Some((value["owner"].as_str().unwrap_or("").to_string(), base64_decode(data)?)))
+}
+
+/// Little-endian reader over account data; every read fails cleanly past the end
+pub struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8], pos: usize) -> Self {
+        Self { data, pos }
+    }
+
+    pub fn bytes(&mut self, n: usize) -> Option<&'a [u8]> {
+        let out = self.data.get(self.pos..self.pos + n)?;
+        self.pos += n;
+        Some(out)
+    }
+
+    pub fn u8(&mut self) -> Option<u8> {
+        Some(self.bytes(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_le_bytes(self.bytes(2)?.try_into().ok()?))
+    }
+
+    pub fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.bytes(4)?.try_into().ok()?))
+    }
+
+    pub fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.bytes(8)?.try_into().ok()?))
+    }
+
+    pub fn pubkey(&mut self) -> Option<String> {
+        Some(bs58_encode(self.bytes(32)?))
+    }
+
+    /// Borsh `Vec<u8>`: u32 length, then the bytes
+    pub fn byte_vec(&mut self) -> Option<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.bytes(len)
+    }
+}
+
+pub fn anchor_discriminator(account: &str) -> [u8; 8] {
+    sha256(format!("account:{}", account).as_bytes())[..8].try_into().unwrap()
+}
+
+/// Squads v4 `Multisig`: create_key, config_authority, threshold u16, time_lock u32,
+/// transaction_index u64, stale_transaction_index u64, rent_collector Option<Pubkey>, bump,
+/// members Vec<{key, permissions mask}>
+fn parse_squads_v4(address: &str, data: &[u8]) -> Option<Control> {
+    if data.get(..8)? != anchor_discriminator("Multisig") {
+        return None;
+    }
+    let mut r = Reader::new(data, 8);
+    r.pubkey()?;
+    let config_authority = r.pubkey()?;
+    let threshold = r.u16()?;
+    let time_lock = r.u32()?;
+    r.u64()?;
+    r.u64()?;
+    if r.u8()? == 1 {
+        r.pubkey()?;
+    }
+    r.u8()?;
+    let count = r.u32()?;
+    let mut voters = 0;
+    for _ in 0..count {
+        r.pubkey()?;
+        // Permissions: 1 initiate, 2 vote, 4 execute
+        if r.u8()? & 2 != 0 {
+            voters += 1;
+        }
+    }
+
+    let mut control = Control::new(ControlKind::SquadsV4);
+    control.account = Some(address.to_string());
+    control.threshold = Some(threshold as u32);
+    control.members = Some(voters);
+    control.timelock_secs = Some(time_lock as u64);
+    if config_authority != DEFAULT_PUBKEY {
+        // A "controlled" multisig: this key can change members and threshold without a vote
+        control.details.push(format!("config authority {}", config_authority));
+    }
+    Some(control)
+}
+
+/// Squads v3 `Ms`: threshold u16, authority_index u16, transaction_index u32, ms_change_index u32,
+/// bump, create_key, allow_external_execute, keys Vec<Pubkey>
+fn parse_squads_v3(address: &str, data: &[u8]) -> Option<Control> {
+    if data.get(..8)? != anchor_discriminator("Ms") {
+        return None;
+    }
+    let mut r = Reader::new(data, 8);
+    let threshold = r.u16()?;
+    r.u16()?;
+    r.u32()?;
+    r.u32()?;
+    r.u8()?;
+    r.pubkey()?;
+    r.u8()?;
+    let members = r.u32()?;
+
+    let mut control = Control::new(ControlKind::SquadsV3);
+    control.account = Some(address.to_string());
+    control.threshold = Some(threshold as u32);
+    control.members = Some(members);
+    control.timelock_secs = Some(0);
+    Some(control)
+}
+
+/// Borsh `VoteThreshold`: 0 YesVotePercentage(u8), 1 QuorumPercentage(u8), 2 Disabled
+fn vote_threshold(r: &mut Reader) -> Option<Option<String>> {
+    Some(match r.u8()? {
+        0 => Some(format!("{}% yes", r.u8()?)),
+        1 => Some(format!("{}% quorum", r.u8()?)),
+        _ => None,
+    })
+}
+
+/// SPL Governance account (types 3, 4, 9, 10 v1 and 18-21 v2): account_type, realm,
+/// governed_account, reserved u32, then GovernanceConfig
+fn parse_governance(address: &str, data: &[u8]) -> Option<Control> {
+    let v2 = match data.first()? {
+        3 | 4 | 9 | 10 => false,
+        18..=21 => true,
+        _ => return None,
+    };
+    let mut r = Reader::new(data, 1);
+    let realm = r.pubkey()?;
+    r.pubkey()?;
+    r.u32()?;
+    let community = vote_threshold(&mut r)?;
+    r.u64()?;
+    let hold_up = r.u32()?;
+    let voting = r.u32()?;
+    r.u8()?;
+    // v1 has one threshold for both mints; what follows it there is proposal_cool_off_time
+    let council = if v2 { vote_threshold(&mut r)? } else { community.clone() };
+
+    let mut control = Control::new(ControlKind::Governance);
+    control.account = Some(address.to_string());
+    control.timelock_secs = Some(hold_up as u64);
+    control.voting_secs = Some(voting as u64);
+    control.details.push(format!("community {}", community.as_deref().unwrap_or("can't vote")));
+    control.details.push(format!("council {}", council.as_deref().unwrap_or("can't vote")));
+    control.details.push(format!("realm {}", realm));
+    Some(control)
+}
+
+/// Decode a multisig or governance account, whatever its owner is
+fn parse_controller(address: &str, owner: &str, data: &[u8]) -> Option<Control> {
+    match owner {
+        SQUADS_V4 => parse_squads_v4(address, data),
+        SQUADS_V3 => parse_squads_v3(address, data),
+        o if GOVERNANCE_PROGRAMS.contains(&o) => parse_governance(address, data),
+        _ => None,
+    }
+}
+
+/// Whether `authority` is the vault/treasury PDA of `candidate` for any known program
+fn controls(candidate: &[u8; 32], authority: &[u8; 32]) -> bool {
+    let squads_v4 = pubkey_bytes(SQUADS_V4).unwrap();
+    let squads_v3 = pubkey_bytes(SQUADS_V3).unwrap();
+    for index in 0..VAULT_INDEXES {
+        if find_program_address(&[b"multisig", candidate, b"vault", &[index]], &squads_v4).0 == *authority {
+            return true;
+        }
+        let index = (index as u32).to_le_bytes();
+        if find_program_address(&[b"squad", candidate, &index, b"authority"], &squads_v3).0 == *authority {
+            return true;
+        }
+    }
+    GOVERNANCE_PROGRAMS.iter().any(|program| {
+        let program = pubkey_bytes(program).unwrap();
+        find_program_address(&[b"native-treasury", candidate], &program).0 == *authority
+    })
+}
+
+/// PDA authority -> the account it belongs to. Resolved once per process: the link never changes.
+fn resolved() -> &'static Mutex<HashMap<String, Option<String>>> {
+    static RESOLVED: OnceLock<Mutex<HashMap<String, Option<String>>>> = OnceLock::new();
+    RESOLVED.get_or_init(Default::default)
+}
+
+/// A PDA stores nothing pointing back at its multisig, so look at the accounts in the
+/// authority's recent transactions and re-derive vault PDAs from each
+async fn resolve_pda(client: &reqwest::Client, authority: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    if let Some(known) = resolved().lock().unwrap().get(authority) {
+        return Ok(known.clone());
+    }
+    let target = pubkey_bytes(authority)?;
+    let mut tried = std::collections::HashSet::new();
+    let mut found = None;
+    'txs: for sig in crate::get_recent_signatures(client, authority, RESOLVE_TXS).await? {
+        let Some(tx) = crate::get_transaction(client, &sig.signature).await? else { continue };
+        for key in tx["transaction"]["message"]["accountKeys"].as_array().into_iter().flatten() {
+            let Some(key) = key["pubkey"].as_str() else { continue };
+            if key == authority || !tried.insert(key.to_string()) {
+                continue;
+            }
+            if pubkey_bytes(key).is_ok_and(|candidate| controls(&candidate, &target)) {
+                found = Some(key.to_string());
+                break 'txs;
+            }
+        }
+    }
+    resolved().lock().unwrap().insert(authority.to_string(), found.clone());
+    Ok(found)
+}
+
+/// Work out who controls `authority`: a plain key, a Squads multisig (the account itself or
+/// one of its vaults) or an SPL Governance account (itself or its native treasury)
+pub async fn identify(rpc_url: &str, authority: &str) -> Result<Control, Box<dyn std::error::Error>> {
+    let client = reqwest::Client::new();
+    if let Some((owner, data)) = get_account(&client, rpc_url, authority).await? {
+        if let Some(control) = parse_controller(authority, &owner, &data) {
+            return Ok(control);
+        }
+    }
+    if is_on_curve(&pubkey_bytes(authority)?) {
+        return Ok(Control::new(ControlKind::HotKey));
+    }
+
+    let Some(account) = resolve_pda(&client, authority).await? else {
+        return Ok(Control::new(ControlKind::UnknownPda));
+    };
+    Ok(get_account(&client, rpc_url, &account).await?
+        .and_then(|(owner, data)| parse_controller(&account, &owner, &data))
+        .unwrap_or_else(|| Control::new(ControlKind::UnknownPda)))
+}
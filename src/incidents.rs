@@ -0,0 +1,286 @@
+use crate::daemon::{Alert, Severity};
+use std::collections::{BTreeMap, HashSet};
+
+/// Alert kinds that describe an ongoing condition: they fire every cycle while true,
+/// and resolve as soon as a check no longer raises them. Other kinds are one-off events.
+const CONDITION_KINDS: &[&str] = &["unexpected_authority", "unexpected_code", "dormant_authority"];
+
+/// An open alert: every occurrence of the same fingerprint until it resolves
+#[derive(Clone, Debug)]
+pub struct Incident {
+    pub id: String,
+    pub subject: String,
+    pub program: String,
+    pub kind: String,
+    pub severity: Severity,
+    pub message: String,
+    pub first_seen: i64,
+    pub last_seen: i64,
+    pub last_notified: i64,
+    pub count: u64,
+    pub acked: bool,
+}
+
+impl Incident {
+    fn open(alert: &Alert, now: i64) -> Self {
+        Self {
+            id: alert.id.clone(),
+            subject: alert.subject.clone(),
+            program: alert.program.clone(),
+            kind: alert.kind.clone(),
+            severity: alert.severity.clone(),
+            message: alert.message.clone(),
+            first_seen: now,
+            last_seen: now,
+            last_notified: now,
+            count: 1,
+            acked: false,
+        }
+    }
+
+    fn is_condition(&self) -> bool {
+        CONDITION_KINDS.contains(&self.kind.as_str())
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.id,
+            "subject": self.subject,
+            "program": self.program,
+            "kind": self.kind,
+            "severity": format!("{:?}", self.severity),
+            "message": self.message,
+            "first_seen": self.first_seen,
+            "last_seen": self.last_seen,
+            "last_notified": self.last_notified,
+            "count": self.count,
+            "acked": self.acked,
+        })
+    }
+
+    pub fn from_json(json: &serde_json::Value) -> Option<Self> {
+        let text = |key: &str| json[key].as_str().map(|v| v.to_string());
+        Some(Self {
+            id: text("id")?,
+            subject: text("subject")?,
+            program: text("program")?,
+            kind: text("kind")?,
+            severity: json["severity"].as_str()?.parse().ok()?,
+            message: text("message")?,
+            first_seen: json["first_seen"].as_i64()?,
+            last_seen: json["last_seen"].as_i64()?,
+            last_notified: json["last_notified"].as_i64()?,
+            count: json["count"].as_u64().unwrap_or(1),
+            acked: json["acked"].as_bool().unwrap_or(false),
+        })
+    }
+}
+
+/// Turns raw per-cycle alerts into notifications: repeats of an open incident are
+/// suppressed for `cooldown_secs`, acked incidents stay silent, and conditions that
+/// clear produce a RESOLVED notice.
+pub struct IncidentTracker {
+    open: BTreeMap<String, Incident>,
+    cooldown_secs: i64,
+}
+
+impl IncidentTracker {
+    pub fn new(open: Vec<Incident>, cooldown_secs: u64) -> Self {
+        Self {
+            open: open.into_iter().map(|i| (i.id.clone(), i)).collect(),
+            cooldown_secs: cooldown_secs as i64,
+        }
+    }
+
+    pub fn restore(&mut self, open: Vec<Incident>) {
+        self.open = open.into_iter().map(|i| (i.id.clone(), i)).collect();
+    }
+
+    pub fn incidents(&self) -> impl Iterator<Item = &Incident> {
+        self.open.values()
+    }
+
+    /// Apply acknowledgements `(id, acked_at)`. An ack only covers an incident that was
+    /// already open when it was given, so a condition that resolves and comes back alerts again.
+    pub fn apply_acks(&mut self, acks: &[(String, i64)]) {
+        for (id, acked_at) in acks {
+            if let Some(incident) = self.open.get_mut(id) {
+                if incident.first_seen <= *acked_at {
+                    incident.acked = true;
+                }
+            }
+        }
+    }
+
+    /// `checked` are the subjects examined this cycle; only their conditions can resolve.
+    /// Info alerts aren't tracked and always pass through.
+    pub fn process(&mut self, alerts: Vec<Alert>, checked: &HashSet<String>, now: i64) -> Vec<Alert> {
+        let mut notify = Vec::new();
+        let firing: HashSet<String> = alerts.iter().map(|a| a.id.clone()).collect();
+
+        for mut alert in alerts {
+            if matches!(alert.severity, Severity::Info) {
+                notify.push(alert);
+                continue;
+            }
+            match self.open.get_mut(&alert.id) {
+                Some(incident) => {
+                    incident.count += 1;
+                    incident.last_seen = now;
+                    incident.severity = alert.severity.clone();
+                    incident.message = alert.message.clone();
+                    if !incident.acked && now - incident.last_notified >= self.cooldown_secs {
+                        incident.last_notified = now;
+                        alert.message = format!("{} (ongoing, seen {}×)", alert.message, incident.count);
+                        notify.push(alert);
+                    }
+                }
+                None => {
+                    self.open.insert(alert.id.clone(), Incident::open(&alert, now));
+                    notify.push(alert);
+                }
+            }
+        }
+
+        let cooldown = self.cooldown_secs;
+        let mut resolved = Vec::new();
+        self.open.retain(|id, incident| {
+            if firing.contains(id) {
+                return true;
+            }
+            if incident.is_condition() {
+                if !checked.contains(&incident.subject) {
+                    return true;
+                }
+                resolved.push(Alert::new(
+                    Severity::Info,
+                    &incident.subject,
+                    &incident.program,
+                    "resolved",
+                    id,
+                    format!("RESOLVED [{}]: {}", id, incident.message),
+                ));
+                return false;
+            }
+            // Events close quietly once they've stopped recurring for a full cooldown
+            now - incident.last_seen < cooldown
+        });
+        notify.extend(resolved);
+        notify
+    }
+}
+
+/// Combine alerts about the same subject into one notification, so an upgrade that also
+/// moves the authority pages once. The combined alert takes the highest severity, and its
+/// id lists the member ids comma-separated so `guardian ack` can take it as given.
+pub fn group(alerts: &[Alert]) -> Vec<Alert> {
+    let mut groups: Vec<Vec<&Alert>> = Vec::new();
+    for alert in alerts {
+        match groups.iter_mut().find(|g| g[0].subject == alert.subject) {
+            Some(group) => group.push(alert),
+            None => groups.push(vec![alert]),
+        }
+    }
+    groups.into_iter().map(|group| {
+        if let [single] = group.as_slice() {
+            return (*single).clone();
+        }
+        let top = group.iter().max_by_key(|a| a.severity.rank()).unwrap();
+        let ids: Vec<&str> = group.iter().map(|a| a.id.as_str()).collect();
+        let mut combined = Alert::new(
+            top.severity.clone(),
+            &top.subject,
+            &top.program,
+            "group",
+            "",
+            group.iter().map(|a| format!("[{}] {}", a.id, a.message)).collect::<Vec<_>>().join(" | "),
+        );
+        combined.id = ids.join(",");
+        combined.timestamp = top.timestamp.clone();
+        combined
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
+
+    fn condition() -> Alert {
+        Alert::new(Severity::High, PROGRAM, "Jupiter", "unexpected_authority", "Evil", "authority is Evil".to_string())
+    }
+
+    fn checked() -> HashSet<String> {
+        [PROGRAM.to_string()].into_iter().collect()
+    }
+
+    #[test]
+    fn repeats_wait_for_cooldown() {
+        let mut tracker = IncidentTracker::new(Vec::new(), 60);
+        assert_eq!(tracker.process(vec![condition()], &checked(), 0).len(), 1);
+        assert!(tracker.process(vec![condition()], &checked(), 30).is_empty());
+        let repeat = tracker.process(vec![condition()], &checked(), 60);
+        assert_eq!(repeat.len(), 1);
+        assert!(repeat[0].message.ends_with("(ongoing, seen 3×)"));
+        assert_eq!(tracker.incidents().next().unwrap().count, 3);
+    }
+
+    #[test]
+    fn acked_incidents_stay_silent() {
+        let mut tracker = IncidentTracker::new(Vec::new(), 60);
+        let id = condition().id;
+        tracker.process(vec![condition()], &checked(), 0);
+        // An ack given before the incident opened doesn't cover it
+        tracker.apply_acks(&[(id.clone(), -1)]);
+        assert_eq!(tracker.process(vec![condition()], &checked(), 60).len(), 1);
+        tracker.apply_acks(&[(id, 100)]);
+        assert!(tracker.process(vec![condition()], &checked(), 1_000).is_empty());
+    }
+
+    #[test]
+    fn conditions_resolve_when_checked_and_clear() {
+        let mut tracker = IncidentTracker::new(Vec::new(), 60);
+        let id = condition().id;
+        tracker.process(vec![condition()], &checked(), 0);
+        // Not checked this cycle: still open
+        assert!(tracker.process(Vec::new(), &HashSet::new(), 10).is_empty());
+        let resolved = tracker.process(Vec::new(), &checked(), 20);
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].kind, "resolved");
+        assert!(resolved[0].message.starts_with(&format!("RESOLVED [{}]", id)));
+        assert_eq!(tracker.incidents().count(), 0);
+        // Coming back opens a new incident that notifies right away
+        assert_eq!(tracker.process(vec![condition()], &checked(), 30).len(), 1);
+    }
+
+    #[test]
+    fn events_close_quietly_after_cooldown() {
+        let mut tracker = IncidentTracker::new(Vec::new(), 60);
+        let upgrade = Alert::new(Severity::High, PROGRAM, "Jupiter", "upgraded", "1234:abcd", "upgraded".to_string());
+        tracker.process(vec![upgrade], &checked(), 0);
+        assert!(tracker.process(Vec::new(), &checked(), 30).is_empty());
+        assert_eq!(tracker.incidents().count(), 1);
+        assert!(tracker.process(Vec::new(), &checked(), 60).is_empty());
+        assert_eq!(tracker.incidents().count(), 0);
+    }
+
+    #[test]
+    fn info_alerts_pass_through_untracked() {
+        let mut tracker = IncidentTracker::new(Vec::new(), 60);
+        let baseline = Alert::new(Severity::Info, PROGRAM, "Jupiter", "baseline", "", "baseline".to_string());
+        assert_eq!(tracker.process(vec![baseline.clone()], &checked(), 0).len(), 1);
+        assert_eq!(tracker.process(vec![baseline], &checked(), 1).len(), 1);
+        assert_eq!(tracker.incidents().count(), 0);
+    }
+
+    #[test]
+    fn groups_are_named_by_their_members() {
+        let upgrade = Alert::new(Severity::Critical, PROGRAM, "Jupiter", "upgraded", "1234:abcd", "upgraded".to_string());
+        let grouped = group(&[condition(), upgrade.clone()]);
+        assert_eq!(grouped.len(), 1);
+        assert_eq!(grouped[0].id, format!("{},{}", condition().id, upgrade.id));
+        assert!(matches!(grouped[0].severity, Severity::Critical));
+        assert_eq!(group(std::slice::from_ref(&upgrade))[0].id, upgrade.id);
+    }
+}
@@ -0,0 +1,454 @@
+use crate::prices::{PriceSource, SOL, WSOL_MINT};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Balance changes below this are float noise, not movements
+const EPSILON: f64 = 1e-12;
+const LONG_TERM_SECS: i64 = 365 * 86_400;
+
+/// Lot matching strategy for disposals
+#[derive(Clone, Copy, Debug)]
+pub enum LotMethod {
+    Fifo,
+    Lifo,
+    Hifo,
+}
+
+impl std::str::FromStr for LotMethod {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "fifo" => Ok(LotMethod::Fifo),
+            "lifo" => Ok(LotMethod::Lifo),
+            "hifo" => Ok(LotMethod::Hifo),
+            other => Err(format!("unknown lot method '{}' (fifo, lifo, hifo)", other)),
+        }
+    }
+}
+
+/// One transaction's effect on the wallet
+pub struct LedgerTx {
+    pub signature: String,
+    pub slot: u64,
+    pub timestamp: i64,
+    /// asset -> signed amount, excluding the fee
+    pub deltas: BTreeMap<String, f64>,
+    /// SOL fee paid by the wallet (0 if someone else paid)
+    pub fee: f64,
+}
+
+struct Lot {
+    acquired: i64,
+    quantity: f64,
+    unit_cost: f64,
+}
+
+/// A disposal matched against one acquisition lot (one row of Form 8949)
+pub struct Disposal {
+    pub signature: String,
+    pub asset: String,
+    pub quantity: f64,
+    /// None when history ran out before the acquisition
+    pub acquired: Option<i64>,
+    pub disposed: i64,
+    pub proceeds: f64,
+    pub cost_basis: f64,
+}
+
+impl Disposal {
+    pub fn gain(&self) -> f64 {
+        self.proceeds - self.cost_basis
+    }
+
+    pub fn is_long_term(&self) -> bool {
+        self.acquired.is_some_and(|a| self.disposed - a > LONG_TERM_SECS)
+    }
+}
+
+pub struct Position {
+    pub asset: String,
+    pub quantity: f64,
+    pub cost_basis: f64,
+    pub price: Option<f64>,
+    pub realized: f64,
+}
+
+impl Position {
+    pub fn unrealized(&self) -> Option<f64> {
+        self.price.map(|p| p * self.quantity - self.cost_basis)
+    }
+}
+
+pub struct Ledger {
+    pub wallet: String,
+    pub method: LotMethod,
+    pub transactions: Vec<LedgerTx>,
+    pub disposals: Vec<Disposal>,
+    pub positions: Vec<Position>,
+    /// Asset movements with no price available (valued at 0)
+    pub unpriced: usize,
+    /// Transactions that could not be fetched, so are missing from the ledger
+    pub unfetched: usize,
+}
+
+/// Fetch up to `limit` transactions and decode the wallet's balance changes, oldest first.
+/// Transfers into an existing token account don't list the owner, so the wallet's token accounts
+/// are walked too. Also returns how many transactions could not be fetched.
+pub async fn fetch_history(
+    client: &reqwest::Client,
+    wallet: &str,
+    limit: usize,
+) -> Result<(Vec<LedgerTx>, usize), Box<dyn std::error::Error>> {
+    let mut addresses = vec![wallet.to_string()];
+    addresses.extend(crate::get_parsed_token_accounts(client, wallet).await?.into_iter().map(|a| a.pubkey));
+
+    let mut sigs: Vec<crate::SignatureInfo> = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    for (i, address) in addresses.iter().enumerate() {
+        eprint!("\r  Listing signatures {}/{} ", i + 1, addresses.len());
+        for sig in crate::get_signature_history(client, address, limit, None).await? {
+            if seen.insert(sig.signature.clone()) {
+                sigs.push(sig);
+            }
+        }
+    }
+    eprintln!();
+    // Newest first across all accounts, like a single address's history
+    sigs.sort_by_key(|s| std::cmp::Reverse(s.slot));
+    sigs.truncate(limit);
+
+    let mut txs = Vec::new();
+    let mut unfetched = 0;
+    for (i, sig) in sigs.iter().rev().enumerate() {
+        eprint!("\r  Decoding transactions {}/{} ", i + 1, sigs.len());
+        let mut tx = crate::get_transaction(client, &sig.signature).await;
+        if !matches!(tx, Ok(Some(_))) {
+            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+            tx = crate::get_transaction(client, &sig.signature).await;
+        }
+        match tx {
+            Ok(Some(tx)) => {
+                if let Some(decoded) = decode_tx(&tx, wallet, &sig.signature, sig.slot) {
+                    txs.push(decoded);
+                }
+            }
+            _ => unfetched += 1,
+        }
+        // Rate limit courtesy
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    }
+    eprintln!();
+
+    Ok((txs, unfetched))
+}
+
+/// The wallet's SOL and token balance deltas from a jsonParsed transaction
+pub fn decode_tx(tx: &serde_json::Value, wallet: &str, signature: &str, slot: u64) -> Option<LedgerTx> {
+    let meta = &tx["meta"];
+    let keys = tx["transaction"]["message"]["accountKeys"].as_array()?;
+    let mut deltas: BTreeMap<String, f64> = BTreeMap::new();
+    let mut fee = 0.0;
+
+    if let Some(idx) = keys.iter().position(|k| k["pubkey"].as_str() == Some(wallet)) {
+        let pre = meta["preBalances"][idx].as_u64().unwrap_or(0) as i128;
+        let post = meta["postBalances"][idx].as_u64().unwrap_or(0) as i128;
+        let mut lamports = post - pre;
+        if idx == 0 {
+            let fee_lamports = meta["fee"].as_u64().unwrap_or(0) as i128;
+            fee = fee_lamports as f64 / crate::LAMPORTS_PER_SOL;
+            lamports += fee_lamports;
+        }
+        *deltas.entry(SOL.to_string()).or_default() += lamports as f64 / crate::LAMPORTS_PER_SOL;
+    }
+
+    let token_total = |field: &str| -> HashMap<String, f64> {
+        let mut totals = HashMap::new();
+        for b in meta[field].as_array().into_iter().flatten() {
+            if b["owner"].as_str() != Some(wallet) { continue; }
+            let mint = b["mint"].as_str().unwrap_or("unknown");
+            let asset = if mint == WSOL_MINT { SOL } else { mint };
+            let amount = b["uiTokenAmount"]["uiAmountString"].as_str()
+                .and_then(|a| a.parse::<f64>().ok())
+                .unwrap_or(0.0);
+            *totals.entry(asset.to_string()).or_insert(0.0) += amount;
+        }
+        totals
+    };
+    let pre = token_total("preTokenBalances");
+    let post = token_total("postTokenBalances");
+    let assets: BTreeSet<&String> = pre.keys().chain(post.keys()).collect();
+    for asset in assets {
+        // wSOL is folded into SOL, so wrapping/unwrapping nets out
+        let change = post.get(asset).unwrap_or(&0.0) - pre.get(asset).unwrap_or(&0.0);
+        *deltas.entry(asset.clone()).or_default() += change;
+    }
+
+    deltas.retain(|_, v| v.abs() > EPSILON);
+    Some(LedgerTx {
+        signature: signature.to_string(),
+        slot,
+        timestamp: tx["blockTime"].as_i64().unwrap_or(0),
+        deltas,
+        fee,
+    })
+}
+
+/// Match disposals against acquisition lots and value everything with `prices`
+pub fn build(
+    wallet: &str,
+    transactions: Vec<LedgerTx>,
+    method: LotMethod,
+    prices: &dyn PriceSource,
+) -> Ledger {
+    let mut lots: BTreeMap<String, Vec<Lot>> = BTreeMap::new();
+    let mut realized: BTreeMap<String, f64> = BTreeMap::new();
+    let mut disposals = Vec::new();
+    let mut unpriced = 0;
+
+    for tx in &transactions {
+        let mut price = |asset: &str| prices.price_at(asset, tx.timestamp).unwrap_or_else(|| {
+            unpriced += 1;
+            0.0
+        });
+
+        // Disposals first so a swap can't match against its own proceeds
+        let mut outflows: Vec<(String, f64)> = tx.deltas.iter()
+            .filter(|(_, v)| **v < 0.0)
+            .map(|(a, v)| (a.clone(), -v))
+            .collect();
+        if tx.fee > 0.0 {
+            outflows.push((SOL.to_string(), tx.fee));
+        }
+        for (asset, quantity) in outflows {
+            let unit_price = price(&asset);
+            let asset_lots = lots.entry(asset.clone()).or_default();
+            for (acquired, qty, cost) in take_lots(asset_lots, quantity, method) {
+                let d = Disposal {
+                    signature: tx.signature.clone(),
+                    asset: asset.clone(),
+                    quantity: qty,
+                    acquired,
+                    disposed: tx.timestamp,
+                    proceeds: qty * unit_price,
+                    cost_basis: cost,
+                };
+                *realized.entry(asset.clone()).or_default() += d.gain();
+                disposals.push(d);
+            }
+        }
+
+        for (asset, quantity) in tx.deltas.iter().filter(|(_, v)| **v > 0.0) {
+            let unit_cost = price(asset);
+            lots.entry(asset.clone()).or_default().push(Lot {
+                acquired: tx.timestamp,
+                quantity: *quantity,
+                unit_cost,
+            });
+        }
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let positions = lots.iter()
+        .map(|(asset, open)| Position {
+            asset: asset.clone(),
+            quantity: open.iter().map(|l| l.quantity).sum(),
+            cost_basis: open.iter().map(|l| l.quantity * l.unit_cost).sum(),
+            price: prices.price_at(asset, now),
+            realized: realized.get(asset).copied().unwrap_or(0.0),
+        })
+        .filter(|p| p.quantity > EPSILON || p.realized.abs() > EPSILON)
+        .collect();
+
+    Ledger { wallet: wallet.to_string(), method, transactions, disposals, positions, unpriced, unfetched: 0 }
+}
+
+/// Remove `quantity` from the lots; returns (acquired, qty, cost) per matched piece.
+/// Any shortfall comes back as one piece with unknown acquisition and zero basis.
+fn take_lots(lots: &mut Vec<Lot>, mut quantity: f64, method: LotMethod) -> Vec<(Option<i64>, f64, f64)> {
+    let mut pieces = Vec::new();
+    while quantity > EPSILON {
+        let pick = match method {
+            LotMethod::Fifo => (!lots.is_empty()).then_some(0),
+            LotMethod::Lifo => lots.len().checked_sub(1),
+            LotMethod::Hifo => lots.iter().enumerate()
+                .max_by(|a, b| a.1.unit_cost.total_cmp(&b.1.unit_cost))
+                .map(|(i, _)| i),
+        };
+        let Some(i) = pick else {
+            pieces.push((None, quantity, 0.0));
+            break;
+        };
+        let lot = &mut lots[i];
+        let used = lot.quantity.min(quantity);
+        pieces.push((Some(lot.acquired), used, used * lot.unit_cost));
+        lot.quantity -= used;
+        quantity -= used;
+        if lot.quantity <= EPSILON {
+            lots.remove(i);
+        }
+    }
+    pieces
+}
+
+fn date_str(ts: i64) -> String {
+    chrono::DateTime::from_timestamp(ts, 0)
+        .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default()
+}
+
+// === CSV export ===
+
+/// Form 8949-style disposal rows
+pub fn csv_8949(ledger: &Ledger) -> String {
+    let mut out = String::from("Description,Date Acquired,Date Sold,Proceeds,Cost Basis,Gain or Loss,Term,Signature\n");
+    for d in &ledger.disposals {
+        let day = |ts: i64| chrono::DateTime::from_timestamp(ts, 0)
+            .map(|t| t.format("%m/%d/%Y").to_string())
+            .unwrap_or_default();
+        out.push_str(&format!("{} {},{},{},{:.2},{:.2},{:.2},{},{}\n",
+            d.quantity, d.asset,
+            d.acquired.map(day).unwrap_or_else(|| "UNKNOWN".to_string()),
+            day(d.disposed),
+            d.proceeds, d.cost_basis, d.gain(),
+            if d.is_long_term() { "Long" } else { "Short" },
+            d.signature));
+    }
+    out
+}
+
+/// Koinly universal format — one row per transaction leg
+pub fn csv_koinly(ledger: &Ledger, prices: &dyn PriceSource) -> String {
+    let mut out = String::from("Date,Sent Amount,Sent Currency,Received Amount,Received Currency,Fee Amount,Fee Currency,Net Worth Amount,Net Worth Currency,Label,Description,TxHash\n");
+    for tx in &ledger.transactions {
+        let sent: Vec<(&String, f64)> = tx.deltas.iter().filter(|(_, v)| **v < 0.0).map(|(a, v)| (a, -v)).collect();
+        let received: Vec<(&String, f64)> = tx.deltas.iter().filter(|(_, v)| **v > 0.0).map(|(a, v)| (a, *v)).collect();
+        let legs = sent.len().max(received.len()).max(1);
+        for i in 0..legs {
+            let s = sent.get(i);
+            let r = received.get(i);
+            let worth = r.or(s).and_then(|(a, q)| prices.price_at(a, tx.timestamp).map(|p| p * q));
+            let fee = if i == 0 && tx.fee > 0.0 { tx.fee.to_string() } else { String::new() };
+            out.push_str(&format!("{},{},{},{},{},{},{},{},{},,slot {},{}\n",
+                date_str(tx.timestamp),
+                s.map(|(_, q)| q.to_string()).unwrap_or_default(),
+                s.map(|(a, _)| a.to_string()).unwrap_or_default(),
+                r.map(|(_, q)| q.to_string()).unwrap_or_default(),
+                r.map(|(a, _)| a.to_string()).unwrap_or_default(),
+                fee,
+                if fee.is_empty() { "" } else { SOL },
+                worth.map(|w| format!("{:.2}", w)).unwrap_or_default(),
+                if worth.is_some() { "USD" } else { "" },
+                tx.slot,
+                tx.signature));
+        }
+    }
+    out
+}
+
+// === Output ===
+
+pub fn to_json(ledger: &Ledger) -> serde_json::Value {
+    serde_json::json!({
+        "address": ledger.wallet,
+        "method": format!("{:?}", ledger.method).to_uppercase(),
+        "transactions": ledger.transactions.len(),
+        "unpriced_movements": ledger.unpriced,
+        "unfetched_transactions": ledger.unfetched,
+        "realized_pnl": ledger.positions.iter().map(|p| p.realized).sum::<f64>(),
+        "unrealized_pnl": ledger.positions.iter().filter_map(|p| p.unrealized()).sum::<f64>(),
+        "positions": ledger.positions.iter().map(|p| serde_json::json!({
+            "asset": p.asset,
+            "quantity": p.quantity,
+            "cost_basis": p.cost_basis,
+            "price": p.price,
+            "realized": p.realized,
+            "unrealized": p.unrealized(),
+        })).collect::<Vec<_>>(),
+        "disposals": ledger.disposals.iter().map(|d| serde_json::json!({
+            "signature": d.signature,
+            "asset": d.asset,
+            "quantity": d.quantity,
+            "acquired": d.acquired,
+            "disposed": d.disposed,
+            "proceeds": d.proceeds,
+            "cost_basis": d.cost_basis,
+            "gain": d.gain(),
+            "long_term": d.is_long_term(),
+        })).collect::<Vec<_>>(),
+    })
+}
+
+pub fn print_ledger(ledger: &Ledger) {
+    let wallet = &ledger.wallet;
+    println!("\n📒 Cost-Basis Ledger: {}...{} ({:?})", &wallet[..8], &wallet[wallet.len()-4..], ledger.method);
+    println!("═══════════════════════════════════════════════════════════════════");
+    println!("  {:16} {:>16} {:>12} {:>12} {:>12}", "Asset", "Held", "Cost Basis", "Realized", "Unrealized");
+    println!("  {:16} {:>16} {:>12} {:>12} {:>12}", "─────", "────", "──────────", "────────", "──────────");
+    for p in &ledger.positions {
+        println!("  {:16} {:>16.6} {:>12.2} {:>12.2} {:>12}",
+            crate::short(&p.asset), p.quantity, p.cost_basis, p.realized,
+            p.unrealized().map(|u| format!("{:.2}", u)).unwrap_or_else(|| "n/a".to_string()));
+    }
+
+    let realized: f64 = ledger.positions.iter().map(|p| p.realized).sum();
+    let unrealized: f64 = ledger.positions.iter().filter_map(|p| p.unrealized()).sum();
+    let unmatched = ledger.disposals.iter().filter(|d| d.acquired.is_none()).count();
+    println!("\n  📊 Summary:");
+    println!("    Transactions: {}", ledger.transactions.len());
+    println!("    Disposals: {}", ledger.disposals.len());
+    println!("    Realized PnL: ${:.2}", realized);
+    println!("    Unrealized PnL: ${:.2}", unrealized);
+    if unmatched > 0 {
+        println!("    ⚠️  {} disposals had no acquisition in the fetched history (zero basis) — it may be older than --limit, or came through a token account closed since", unmatched);
+    }
+    if ledger.unfetched > 0 {
+        println!("    ⚠️  {} transactions could not be fetched and are missing — PnL is incomplete, run again", ledger.unfetched);
+    }
+    if ledger.unpriced > 0 {
+        println!("    ⚠️  {} movements had no price (valued at $0) — supply --prices <file.csv>", ledger.unpriced);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two units each, bought at t=1 for $10, t=2 for $30 and t=3 for $20
+    fn lots() -> Vec<Lot> {
+        [(1, 10.0), (2, 30.0), (3, 20.0)].into_iter()
+            .map(|(acquired, unit_cost)| Lot { acquired, quantity: 2.0, unit_cost })
+            .collect()
+    }
+
+    fn remaining(lots: &[Lot]) -> Vec<(i64, f64)> {
+        lots.iter().map(|l| (l.acquired, l.quantity)).collect()
+    }
+
+    #[test]
+    fn fifo_takes_oldest_first() {
+        let mut lots = lots();
+        assert_eq!(take_lots(&mut lots, 3.0, LotMethod::Fifo), vec![(Some(1), 2.0, 20.0), (Some(2), 1.0, 30.0)]);
+        assert_eq!(remaining(&lots), vec![(2, 1.0), (3, 2.0)]);
+    }
+
+    #[test]
+    fn lifo_takes_newest_first() {
+        let mut lots = lots();
+        assert_eq!(take_lots(&mut lots, 3.0, LotMethod::Lifo), vec![(Some(3), 2.0, 40.0), (Some(2), 1.0, 30.0)]);
+        assert_eq!(remaining(&lots), vec![(1, 2.0), (2, 1.0)]);
+    }
+
+    #[test]
+    fn hifo_takes_costliest_first() {
+        let mut lots = lots();
+        assert_eq!(take_lots(&mut lots, 3.0, LotMethod::Hifo), vec![(Some(2), 2.0, 60.0), (Some(3), 1.0, 20.0)]);
+        assert_eq!(remaining(&lots), vec![(1, 2.0), (3, 1.0)]);
+    }
+
+    #[test]
+    fn shortfall_has_no_basis() {
+        let mut lots = lots();
+        let pieces = take_lots(&mut lots, 7.0, LotMethod::Fifo);
+        assert_eq!(pieces.last(), Some(&(None, 1.0, 0.0)));
+        assert!(lots.is_empty());
+    }
+}
@@ -4,29 +4,100 @@ mod web;
 mod audit;
 mod scanner;
 mod authority;
+mod buffers;
 mod daemon;
+mod encoding;
+mod tx;
+mod rent;
+mod approvals;
+mod snapshot;
+mod prices;
+mod ledger;
+mod crypto;
+mod sns;
+mod portfolio;
+mod watch;
+mod alerts;
+mod store;
+mod watchlist;
+mod api;
+mod incidents;
+mod activity;
+mod governance;
+mod proposals;
+mod sbpf;
+mod rules;
+mod deploys;
 
 fn rpc_url() -> String {
     env::var("SOLANA_RPC_URL").unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string())
 }
 const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
 
+/// Local state directory (snapshots, cursors, guardian state)
+fn data_dir() -> std::path::PathBuf {
+    match env::var("SOLSCAN_HOME") {
+        Ok(dir) => dir.into(),
+        Err(_) => std::path::PathBuf::from(env::var("HOME").unwrap_or_else(|_| ".".to_string())).join(".solscan"),
+    }
+}
+
+/// An address or signature abbreviated for display, e.g. "JUP6LkbZ...TaV4"
+fn short(s: &str) -> String {
+    if s.len() <= 12 {
+        return s.to_string();
+    }
+    format!("{}...{}", &s[..8], &s[s.len() - 4..])
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
     if args.len() < 2 {
-        eprintln!("Usage: solscan <wallet_address> [OPTIONS]");
+        eprintln!("Usage: solscan <wallet_address | name.sol> [OPTIONS]");
         eprintln!("\nScan any Solana wallet from the command line.");
         eprintln!("\nOptions:");
         eprintln!("  --tokens          Show all token accounts and balances");
-        eprintln!("  --history         Show recent transaction history");
+        eprintln!("  --history         Show recent transaction history (with --audit: deploys, upgrades and authority changes)");
         eprintln!("  --json            Output as JSON");
         eprintln!("  --defi            Show DeFi positions (mSOL, jitoSOL)");
-        eprintln!("  --watch           Live monitoring mode (poll for changes)");
+        eprintln!("  --watch           Live monitoring mode (SOL, token balances, new transactions)");
+        eprintln!("                    (resumes after the last reported tx; cursor kept in $SOLSCAN_HOME/watch)");
         eprintln!("  --interval <N>    Poll interval in seconds (default: 5)");
+        eprintln!("  --programs <FILE> Program watchlist for --guardian/--scan-defi/--power-map (default: $SOLSCAN_HOME/programs.json)");
+        eprintln!("  --listen <ADDR>   With --guardian: serve /metrics, /status, /alerts (e.g. 127.0.0.1:9464)");
+        eprintln!("  --cooldown <N>    With --guardian: seconds between repeats of an open alert (default: 3600)");
+        eprintln!("  --alerts <FILE>   Alert sinks for --watch/--guardian (default: $SOLSCAN_HOME/alerts.json)");
+        eprintln!("  --rules <FILE>    Audit rule weights, severities and allowlists for --audit/--scan-defi/--guardian (default: $SOLSCAN_HOME/rules.json)");
+        eprintln!("  --verify <FILE>   With --audit: compare the deployed ELF with a local .so build (exit 1 on mismatch)");
+        eprintln!("  --rent            Empty/dust token accounts and reclaimable rent");
+        eprintln!("  --dust <N>        Dust threshold in token units for --rent (default: 0.001)");
+        eprintln!("  --close-tx        With --rent: emit unsigned close-account transactions");
+        eprintln!("  --approvals       Token delegations, close authorities and owner changes");
+        eprintln!("  --ledger          Cost-basis ledger with realized/unrealized PnL");
+        eprintln!("  --method <M>      Lot matching for --ledger: fifo, lifo, hifo (default: fifo)");
+        eprintln!("  --prices <FILE>   Historical prices CSV: asset,date,usd (asset = mint or SOL)");
+        eprintln!("  --limit <N>       Transactions to walk for --ledger and --audit --history (default: 1000)");
+        eprintln!("  --csv <FILE>      Export --ledger as CSV");
+        eprintln!("  --csv-format <F>  8949 (disposals, default) or koinly (transactions)");
+        eprintln!("\nCommands:");
+        eprintln!("  solscan snapshot save <wallet>              Save SOL/tokens/stake/DeFi to ~/.solscan");
+        eprintln!("  solscan snapshot list <wallet>              List saved snapshots");
+        eprintln!("  solscan snapshot diff <wallet> [FROM [TO]]  Diff snapshots (latest, slot, or YYYY-MM-DD; TO defaults to now)");
+        eprintln!("  solscan portfolio <file.json>               Scan a labelled wallet group concurrently");
+        eprintln!("                    [--concurrency <N>]       Parallel wallet scans (default: 8)");
+        eprintln!("  solscan guardian history                    Past guardian alerts, newest first");
+        eprintln!("                    [--program <NAME>] [--severity <LEVEL>] [--since YYYY-MM-DD] [--limit <N>]");
+        eprintln!("  solscan guardian incidents                  Open guardian alerts and their ids");
+        eprintln!("  solscan guardian ack <id>                   Silence an open alert until it resolves");
+        eprintln!("  solscan rules                               Audit rules with their effective weights [--rules <FILE>]");
+        eprintln!("  solscan watch <wallet|file.json>...         Watch many wallets in one process (watchlist = portfolio file + rules)");
+        eprintln!("                    [--min-sol <N>]           Ignore SOL changes smaller than N per poll");
+        eprintln!("                    [--outgoing-only]         Only balance decreases and transactions the wallet signed");
         eprintln!("\nExamples:");
         eprintln!("  solscan EXEDJvuA...epTq --tokens");
         eprintln!("  solscan EXEDJvuA...epTq --watch --interval 10");
         eprintln!("  solscan EXEDJvuA...epTq --watch --json | jq '.change'");
+        eprintln!("  solscan EXEDJvuA...epTq --rent --close-tx");
         eprintln!("  solscan x --power-map              # Who controls Solana DeFi");
         eprintln!("  solscan x --scan-defi              # Audit top 15 protocols");
         eprintln!("  solscan x --guardian                # Autonomous monitoring daemon");
@@ -46,6 +117,25 @@ fn main() {
     let scan_all = args.contains(&"--scan-defi".to_string());
     let power_map = args.contains(&"--power-map".to_string());
     let guardian_mode = args.contains(&"--guardian".to_string());
+    let rent_mode = args.contains(&"--rent".to_string());
+    let approvals_mode = args.contains(&"--approvals".to_string());
+    let ledger_mode = args.contains(&"--ledger".to_string());
+    let flag_value = |flag: &str| args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+    let watch_rules = watch::WatchRules {
+        min_sol: flag_value("--min-sol").and_then(|v| v.parse().ok()).unwrap_or(0.0),
+        outgoing_only: args.contains(&"--outgoing-only".to_string()),
+        ..Default::default()
+    };
+    let ledger_limit: usize = flag_value("--limit").and_then(|v| v.parse().ok()).unwrap_or(1000);
+    let close_tx = args.contains(&"--close-tx".to_string());
+    let dust_threshold: f64 = args.iter()
+        .position(|a| a == "--dust")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0.001);
     let guardian_interval: u64 = args.iter()
         .position(|a| a == "--every")
         .and_then(|i| args.get(i + 1))
@@ -66,60 +156,207 @@ fn main() {
     // Collect extra wallets for analyze mode
     let extra_wallets: Vec<String> = if analyze_mode {
         args.iter().skip(2)
-            .filter(|a| !a.starts_with("--") && a.len() > 30)
+            .filter(|a| !a.starts_with("--") && (a.len() > 30 || sns::is_domain(a)))
             .cloned().collect()
     } else { vec![] };
 
     rt.block_on(async {
-        if guardian_mode {
-            daemon::run_daemon(&rpc_url(), guardian_interval, output_json).await;
+        // name.sol is accepted anywhere a wallet address is
+        let resolved = match sns::resolve_address(&reqwest::Client::new(), wallet).await {
+            Ok(address) => address,
+            Err(e) => { eprintln!("Error: {}", e); std::process::exit(1); }
+        };
+        let wallet = &resolved;
+
+        if wallet == "snapshot" {
+            if let Err(e) = run_snapshot(&args[2..], output_json).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        } else if wallet == "guardian" {
+            if let Err(e) = run_guardian_command(&args[2..], output_json) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        } else if wallet == "rules" {
+            print_rules(&load_rules(flag_value("--rules")), output_json);
+        } else if wallet == "watch" {
+            let concurrency = flag_value("--concurrency").and_then(|v| v.parse().ok()).unwrap_or(8);
+            let router = load_alert_router(flag_value("--alerts")).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            if let Err(e) = run_watch(&args[2..], watch_interval, concurrency, &watch_rules, router, output_json).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        } else if wallet == "portfolio" {
+            let concurrency = flag_value("--concurrency").and_then(|v| v.parse().ok()).unwrap_or(8);
+            if let Err(e) = run_portfolio(&args[2..], concurrency, output_json).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        } else if guardian_mode {
+            let router = load_alert_router(flag_value("--alerts")).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            let source = watchlist::Source::resolve(flag_value("--programs"));
+            let programs = source.load().unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            let options = daemon::GuardianOptions {
+                interval_secs: guardian_interval,
+                cooldown_secs: flag_value("--cooldown").and_then(|v| v.parse().ok()).unwrap_or(3600),
+                json_output: output_json,
+                listen: flag_value("--listen"),
+                rules: load_rules(flag_value("--rules")),
+            };
+            daemon::run_daemon(&rpc_url(), source, programs, &router, options).await;
         } else if power_map {
-            let results = authority::map_all_authorities(&rpc_url()).await;
+            let programs = watchlist::Source::resolve(flag_value("--programs")).load().unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            let results = authority::map_all_authorities(&rpc_url(), &programs).await;
             if output_json {
                 let json: Vec<_> = results.iter().map(|r| serde_json::json!({
                     "name": r.program_name,
                     "program_id": r.program_id,
+                    "loader": r.loader.map(|l| l.name()),
+                    "mutability": r.mutability,
                     "upgrade_authority": r.upgrade_authority,
                     "programdata": r.programdata_account,
                     "authority_balance_sol": r.authority_sol_balance,
                     "authority_tx_count": r.authority_tx_count,
+                    "deploy_slot": r.deploy_slot,
+                    "code_hash": r.code_hash,
+                    "control": r.control.as_ref().map(|c| c.to_json()),
                 })).collect();
                 println!("{}", serde_json::to_string_pretty(&json).unwrap());
             } else {
                 authority::print_power_map(&results);
             }
         } else if scan_all {
-            let results = scanner::scan_all(&rpc_url()).await;
+            let programs = watchlist::Source::resolve(flag_value("--programs")).load().unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            let results = scanner::scan_all(&rpc_url(), &programs, load_rules(flag_value("--rules"))).await;
             if output_json {
                 let json: Vec<_> = results.iter().map(|(name, r)| serde_json::json!({
                     "name": name, "program_id": r.program_id,
-                    "upgradeable": r.is_upgradeable, "risk_score": r.risk_score,
-                    "warnings": r.warnings,
+                    "upgradeable": r.is_upgradeable, "mutability": r.program.mutability(), "risk_score": r.risk_score,
+                    "warnings": r.warnings(),
+                    "findings": r.findings.iter().map(|f| f.to_json()).collect::<Vec<_>>(),
+                    "control": r.control.as_ref().map(|c| c.to_json()),
+                    "security_txt": r.elf.as_ref().and_then(|e| e.security_txt.as_ref()).map(|t| t.to_json()),
                 })).collect();
                 println!("{}", serde_json::to_string_pretty(&json).unwrap());
             } else {
                 scanner::print_report(&results);
             }
         } else if audit_mode {
-            let auditor = audit::ContractAudit::new(rpc_url());
-            match auditor.audit(wallet).await {
-                Ok(result) => {
+            let auditor = audit::ContractAudit::new(rpc_url(), load_rules(flag_value("--rules")));
+            let client = reqwest::Client::new();
+            // One fetch of the program and its code serves the audit, --verify and --history
+            match authority::load_program(&client, &rpc_url(), wallet).await {
+                Ok(program) => {
+                    let result = auditor.audit_program(wallet, &program).await;
+                    // Code the upgrade authority has staged but not deployed yet
+                    let upgradeable_loader = program.loader == Some(authority::Loader::Upgradeable);
+                    let (deployed_hash, buffers) = if upgradeable_loader {
+                        pending_buffers(&program).await
+                    } else {
+                        (None, None)
+                    };
+                    let verification = match flag_value("--verify") {
+                        Some(path) => match auditor.verify(wallet, &program, &path) {
+                            Ok(v) => Some(v),
+                            Err(e) => { eprintln!("Verify error: {}", e); std::process::exit(1); }
+                        },
+                        None => None,
+                    };
+                    let history = if show_history && upgradeable_loader {
+                        match deploys::history(&client, &rpc_url(), wallet, &program, ledger_limit).await {
+                            Ok(events) => Some(events),
+                            Err(e) => { eprintln!("History error: {}", e); None }
+                        }
+                    } else {
+                        None
+                    };
                     if output_json {
                         println!("{}", serde_json::json!({
                             "program_id": result.program_id,
                             "executable": result.is_executable,
                             "upgradeable": result.is_upgradeable,
                             "owner": result.owner,
+                            "loader": result.program.loader.map(|l| l.name()),
+                            "mutability": result.program.mutability(),
+                            "loader_status": result.program.status.map(|s| s.name()),
+                            "next_version": result.program.next_version,
+                            "builtin": result.program.builtin,
                             "data_size": result.data_size,
                             "risk_score": result.risk_score,
-                            "warnings": result.warnings,
+                            "warnings": result.warnings(),
+                            "findings": result.findings.iter().map(|f| f.to_json()).collect::<Vec<_>>(),
+                            "upgrade_authority": result.upgrade_authority,
+                            "control": result.control.as_ref().map(|c| c.to_json()),
+                            "bytecode": result.elf.as_ref().map(|e| e.to_json()),
+                            "code_hash": deployed_hash,
+                            "buffers": buffers.as_ref().map(|b| b.iter().map(|b| {
+                                let mut json = b.to_json();
+                                json["status"] = serde_json::json!(b.compare(deployed_hash.as_deref()));
+                                json
+                            }).collect::<Vec<_>>()),
+                            "verification": verification.as_ref().map(|v| v.to_json()),
+                            "history": history.as_ref().map(|h| h.iter().map(|e| e.to_json()).collect::<Vec<_>>()),
                         }));
                     } else {
                         audit::print_audit(&result);
+                        if let Some(buffers) = &buffers {
+                            buffers::print_buffers(buffers, deployed_hash.as_deref());
+                        }
+                        if let Some(events) = &history {
+                            deploys::print_history(events);
+                        }
+                        if let Some(v) = &verification {
+                            audit::print_verification(v);
+                        }
+                    }
+                    if verification.is_some_and(|v| !v.matched()) {
+                        std::process::exit(1);
                     }
                 }
                 Err(e) => { eprintln!("Audit error: {}", e); std::process::exit(1); }
             }
+        } else if rent_mode {
+            if let Err(e) = run_rent(wallet, dust_threshold, close_tx, output_json).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        } else if ledger_mode {
+            let method = match flag_value("--method").unwrap_or_else(|| "fifo".to_string()).parse() {
+                Ok(m) => m,
+                Err(e) => { eprintln!("Error: {}", e); std::process::exit(1); }
+            };
+            let options = LedgerOptions {
+                method,
+                limit: ledger_limit,
+                prices_file: flag_value("--prices"),
+                csv_path: flag_value("--csv"),
+                csv_format: flag_value("--csv-format").unwrap_or_else(|| "8949".to_string()),
+            };
+            if let Err(e) = run_ledger(wallet, &options, output_json).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        } else if approvals_mode {
+            if let Err(e) = run_approvals(wallet, output_json).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
         } else if web_mode {
             if let Err(e) = run_web(wallet, web_depth, output_json).await {
                 eprintln!("Error: {}", e);
@@ -131,7 +368,15 @@ fn main() {
                 std::process::exit(1);
             }
         } else if watch_mode {
-            if let Err(e) = watch_wallet(wallet, watch_interval, output_json).await {
+            let target = watch::WatchTarget {
+                entry: portfolio::PortfolioEntry { address: wallet.clone(), label: watch_label(&args[1], wallet), tags: vec![] },
+                rules: watch_rules.clone(),
+            };
+            let router = load_alert_router(flag_value("--alerts")).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            if let Err(e) = watch::watch(vec![target], watch_interval, 1, router, output_json).await {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
@@ -153,6 +398,7 @@ async fn scan_wallet(
 
     // 1. Get SOL balance
     let balance = get_sol_balance(&client, wallet).await?;
+    let domain = sns::primary_domain(&client, wallet).await;
 
     // Collect data
     let tokens = if show_tokens || output_json { get_token_accounts(&client, wallet).await.unwrap_or_default() } else { vec![] };
@@ -161,15 +407,14 @@ async fn scan_wallet(
     if output_json {
         let mut json = serde_json::json!({
             "address": wallet,
+            "domain": domain,
             "sol_balance": balance,
         });
-        if show_tokens || true {
-            json["tokens"] = serde_json::json!(tokens.iter().map(|t| serde_json::json!({
-                "mint": t.mint,
-                "balance": t.ui_amount,
-                "decimals": t.decimals
-            })).collect::<Vec<_>>());
-        }
+        json["tokens"] = serde_json::json!(tokens.iter().map(|t| serde_json::json!({
+            "mint": t.mint,
+            "balance": t.ui_amount,
+            "decimals": t.decimals
+        })).collect::<Vec<_>>());
         if show_history {
             json["transactions"] = serde_json::json!(signatures.iter().map(|s| serde_json::json!({
                 "signature": s.signature,
@@ -187,6 +432,9 @@ async fn scan_wallet(
     println!("║  🔍 Solana Wallet Scanner                                   ║");
     println!("╠══════════════════════════════════════════════════════════════╣");
     println!("║  Address: {}...{}", &wallet[..8], &wallet[wallet.len()-8..]);
+    if let Some(domain) = &domain {
+        println!("║  Domain: {}", domain);
+    }
     println!("║  SOL Balance: {:.6} SOL", balance);
     println!("╚══════════════════════════════════════════════════════════════╝");
 
@@ -196,8 +444,8 @@ async fn scan_wallet(
         if tokens.is_empty() {
             println!("  No token accounts found.");
         } else {
-            println!("  {:>12}  {:>10}  {}", "Balance", "Decimals", "Mint");
-            println!("  {:>12}  {:>10}  {}", "───────", "────────", "────");
+            println!("  {:>12}  {:>10}  Mint", "Balance", "Decimals");
+            println!("  {:>12}  {:>10}  ────", "───────", "────────");
             for token in &tokens {
                 println!(
                     "  {:>12}  {:>10}  {}...{}",
@@ -256,6 +504,8 @@ async fn run_web(wallet: &str, max_depth: usize, json_output: bool) -> Result<()
     println!("🕸️  SolWeb — Crawling from {}...{} (depth: {})", &wallet[..8], &wallet[wallet.len()-4..], max_depth);
     let mut spider = web::SolWeb::new(rpc_url(), max_depth);
     spider.crawl(wallet).await?;
+    let wallets: Vec<String> = spider.wallet_tokens.keys().cloned().collect();
+    let domains = sns::primary_domains(&reqwest::Client::new(), &wallets).await;
     
     if json_output {
         let out = serde_json::json!({
@@ -263,97 +513,388 @@ async fn run_web(wallet: &str, max_depth: usize, json_output: bool) -> Result<()
             "tokens": spider.token_holders.len(),
             "wallet_tokens": spider.wallet_tokens,
             "token_holders": spider.token_holders,
+            "domains": domains,
         });
         println!("{}", serde_json::to_string_pretty(&out)?);
     } else {
-        spider.print_web();
+        spider.print_web(&domains);
     }
     Ok(())
 }
 
-// === Analyze Mode ===
+// === Rent Reclaim Mode ===
 
-async fn run_analyze(primary: &str, others: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+async fn run_rent(wallet: &str, dust_threshold: f64, close_tx: bool, json_output: bool) -> Result<(), Box<dyn std::error::Error>> {
     let client = reqwest::Client::new();
-    let mut graph = analyze::WalletGraph::new();
+    let report = rent::rent_report(&client, wallet, dust_threshold).await?;
+    let transactions = if close_tx { rent::close_transactions(&client, &report).await? } else { vec![] };
 
-    let mut all_wallets = vec![primary.to_string()];
-    all_wallets.extend(others.iter().cloned());
+    if json_output {
+        let mut out = rent::to_json(&report);
+        if close_tx {
+            out["close_transactions"] = serde_json::json!(transactions);
+        }
+        println!("{}", serde_json::to_string_pretty(&out)?);
+    } else {
+        rent::print_report(&report);
+        if close_tx {
+            println!("\n  ✍️  Unsigned close transactions (base64, sign with {}...{}):", &wallet[..8], &wallet[wallet.len()-4..]);
+            for tx in &transactions {
+                println!("{}", tx);
+            }
+        }
+    }
+    Ok(())
+}
 
-    for wallet in &all_wallets {
-        eprint!("  Scanning {}...{} ", &wallet[..8], &wallet[wallet.len()-4..]);
-        let balance = get_sol_balance(&client, wallet).await.unwrap_or(0.0);
-        let tokens = get_token_accounts(&client, wallet).await.unwrap_or_default();
-        let mints: Vec<String> = tokens.iter().map(|t| t.mint.clone()).collect();
-        eprintln!("({:.4} SOL, {} tokens)", balance, mints.len());
-        graph.add_wallet(wallet.clone(), balance, mints);
-        // Rate limit courtesy
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+// === Approvals Mode ===
+
+async fn run_approvals(wallet: &str, json_output: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let client = reqwest::Client::new();
+    let report = approvals::approval_report(&client, wallet).await?;
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&approvals::to_json(&report))?);
+    } else {
+        approvals::print_report(&report);
+    }
+    Ok(())
+}
+
+// === Ledger Mode ===
+
+struct LedgerOptions {
+    method: ledger::LotMethod,
+    limit: usize,
+    prices_file: Option<String>,
+    csv_path: Option<String>,
+    csv_format: String,
+}
+
+async fn run_ledger(wallet: &str, options: &LedgerOptions, json_output: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let client = reqwest::Client::new();
+    let (transactions, unfetched) = ledger::fetch_history(&client, wallet, options.limit).await?;
+
+    let mut assets: Vec<String> = transactions.iter().flat_map(|t| t.deltas.keys().cloned()).collect();
+    assets.sort();
+    assets.dedup();
+
+    let mut prices = prices::PriceChain::new();
+    if let Some(path) = &options.prices_file {
+        prices = prices.with(prices::CsvPrices::load(path)?);
+    }
+    prices = prices.with(prices::Stablecoins);
+    match prices::JupiterPrices::fetch(&assets).await {
+        Ok(spot) => prices = prices.with(spot),
+        Err(e) => eprintln!("  ⚠️  Spot prices unavailable: {}", e),
     }
 
-    analyze::print_analysis(&graph);
+    let mut ledger = ledger::build(wallet, transactions, options.method, &prices);
+    ledger.unfetched = unfetched;
+
+    if let Some(path) = &options.csv_path {
+        let csv = match options.csv_format.as_str() {
+            "8949" => ledger::csv_8949(&ledger),
+            "koinly" => ledger::csv_koinly(&ledger, &prices),
+            other => return Err(format!("unknown CSV format '{}' (8949, koinly)", other).into()),
+        };
+        std::fs::write(path, csv)?;
+        eprintln!("  💾 Wrote {} CSV to {}", options.csv_format, path);
+    }
+
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&ledger::to_json(&ledger))?);
+    } else {
+        ledger::print_ledger(&ledger);
+    }
+    Ok(())
+}
+
+// === Portfolio Mode ===
+
+async fn run_portfolio(args: &[String], concurrency: usize, json_output: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let path = args.first().filter(|a| !a.starts_with("--"))
+        .ok_or("usage: solscan portfolio <file.json>")?;
+    let mut entries = portfolio::load(path)?;
+    portfolio::resolve_entries(&reqwest::Client::new(), &mut entries).await?;
+
+    eprintln!("  Scanning {} wallets ({} at a time)...", entries.len(), concurrency);
+    let results = portfolio::scan(&entries, concurrency).await;
+    let report = portfolio::aggregate(results);
+
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&portfolio::to_json(&report))?);
+    } else {
+        portfolio::print_report(&report);
+    }
     Ok(())
 }
 
+// === Audit ===
+
+/// Deployed code hash and the authority's deploy buffers; `None` where the lookup failed
+async fn pending_buffers(program: &authority::Program) -> (Option<String>, Option<Vec<buffers::Buffer>>) {
+    let code_hash = program.code_hash();
+    let Some(authority) = &program.authority else {
+        return (code_hash, Some(Vec::new()));
+    };
+    match buffers::find_buffers(&rpc_url(), authority).await {
+        Ok(buffers) => (code_hash, Some(buffers)),
+        Err(e) => {
+            eprintln!("⚠️  Could not list deploy buffers: {}", e);
+            (code_hash, None)
+        }
+    }
+}
+
+// === Guardian ===
+
+fn run_guardian_command(args: &[String], json_output: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let flag = |name: &str| args.iter().position(|a| a == name).and_then(|i| args.get(i + 1));
+    match args.first().map(|a| a.as_str()) {
+        Some("history") => {
+            let program = flag("--program").map(|p| p.to_lowercase());
+            let min_severity: Option<daemon::Severity> = flag("--severity").map(|s| s.parse()).transpose()?;
+            let since = match flag("--since") {
+                Some(date) => Some(chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .map_err(|_| format!("--since expects YYYY-MM-DD, got '{}'", date))?
+                    .and_hms_opt(0, 0, 0).map(|t| t.and_utc().timestamp()).unwrap_or(0)),
+                None => None,
+            };
+            let limit: usize = flag("--limit").and_then(|v| v.parse().ok()).unwrap_or(50);
+
+            let mut history = store::GuardianStore::open()?.alerts()?;
+            history.retain(|a| {
+                program.as_ref().is_none_or(|p| a.alert.program.to_lowercase().contains(p.as_str()))
+                    && min_severity.as_ref().is_none_or(|s| a.alert.severity.rank() >= s.rank())
+                    && since.is_none_or(|ts| a.recorded_at >= ts)
+            });
+            history.reverse();
+            history.truncate(limit);
+
+            if json_output {
+                let json: Vec<_> = history.iter().map(|a| a.to_json()).collect();
+                println!("{}", serde_json::to_string_pretty(&json)?);
+            } else if history.is_empty() {
+                println!("No guardian alerts recorded");
+            } else {
+                println!("\n📜 Guardian alert history ({} shown)", history.len());
+                for a in &history {
+                    println!("  {}  {} [{}] {} ({})", a.time_str(), a.alert.severity, a.alert.program, a.alert.message, a.alert.id);
+                }
+            }
+            Ok(())
+        }
+        Some("incidents") => {
+            let store = store::GuardianStore::open()?;
+            let incidents = store.load_state()?.map(|s| s.incidents).unwrap_or_default();
+            if json_output {
+                let json: Vec<_> = incidents.iter().map(|i| i.to_json()).collect();
+                println!("{}", serde_json::to_string_pretty(&json)?);
+            } else if incidents.is_empty() {
+                println!("No open guardian alerts");
+            } else {
+                let acks = store.acks()?;
+                println!("\n🚨 Open guardian alerts ({})", incidents.len());
+                for i in &incidents {
+                    let first_seen = chrono::DateTime::from_timestamp(i.first_seen, 0)
+                        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_default();
+                    // Acks given since the daemon last saved show up right away
+                    let acked = i.acked || acks.iter().any(|(id, at)| *id == i.id && *at >= i.first_seen);
+                    println!("  {}  {} [{}] {}", i.id, i.severity, i.program, i.message);
+                    println!("      since {}, seen {}×{}", first_seen, i.count, if acked { ", acked" } else { "" });
+                }
+            }
+            Ok(())
+        }
+        Some("ack") => {
+            let id = args.get(1).ok_or("usage: solscan guardian ack <alert-id>")?;
+            let store = store::GuardianStore::open()?;
+            let incidents = store.load_state()?.map(|s| s.incidents).unwrap_or_default();
+            // A grouped notification's id is its members' ids, comma-separated
+            let open: Vec<_> = id.split(',')
+                .filter_map(|member| incidents.iter().find(|i| i.id == member))
+                .collect();
+            if open.is_empty() {
+                return Err(format!("no open alert with id '{}' (see solscan guardian incidents)", id).into());
+            }
+            for incident in open {
+                store.append_ack(&incident.id)?;
+                println!("✅ Acknowledged {} [{}] {}", incident.id, incident.program, incident.message);
+            }
+            Ok(())
+        }
+        _ => Err("usage: solscan guardian <history|incidents|ack> ...".into()),
+    }
+}
+
+// === Alerting ===
+
+/// The audit rules with the rules file applied
+fn print_rules(rules: &rules::RuleSet, json_output: bool) {
+    let effective = rules.effective();
+    if json_output {
+        let json: Vec<_> = effective.iter().map(|(rule, enabled, severity, weight)| serde_json::json!({
+            "id": rule.id, "enabled": enabled, "severity": severity.name(), "weight": weight, "title": rule.title,
+        })).collect();
+        println!("{}", serde_json::to_string_pretty(&json).unwrap());
+        return;
+    }
+    println!("\n📏 Audit rules");
+    println!("  {:26} {:8} {:>6}  Checks", "Rule", "Severity", "Weight");
+    for (rule, enabled, severity, weight) in effective {
+        let weight = if enabled { weight.to_string() } else { "off".to_string() };
+        println!("  {:26} {:8} {:>6}  {}", rule.id, severity.name(), weight, rule.title);
+    }
+}
+
+/// Audit rules from `--rules <file>`, else `$SOLSCAN_HOME/rules.json`; a bad file is fatal
+fn load_rules(path: Option<String>) -> rules::RuleSet {
+    rules::RuleSet::resolve(path).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Alert sinks from `--alerts <file>`, else `$SOLSCAN_HOME/alerts.json` if present
+fn load_alert_router(path: Option<String>) -> Result<alerts::AlertRouter, Box<dyn std::error::Error>> {
+    match path {
+        Some(path) => alerts::AlertRouter::load(&path),
+        None => {
+            let default = data_dir().join("alerts.json");
+            if default.exists() {
+                alerts::AlertRouter::load(&default.to_string_lossy())
+            } else {
+                Ok(alerts::AlertRouter::default())
+            }
+        }
+    }
+}
+
 // === Watch Mode ===
 
-async fn watch_wallet(
-    wallet: &str,
+/// Label for a wallet given on the command line: the domain if one was typed, else a short address
+fn watch_label(input: &str, address: &str) -> String {
+    if sns::is_domain(input) {
+        input.to_string()
+    } else {
+        format!("{}...{}", &address[..4.min(address.len())], &address[address.len().saturating_sub(4)..])
+    }
+}
+
+async fn run_watch(
+    args: &[String],
     interval_secs: u64,
+    concurrency: usize,
+    rules: &watch::WatchRules,
+    router: alerts::AlertRouter,
     json_output: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let client = reqwest::Client::new();
-    let mut last_balance: f64 = -1.0;
-    let mut last_sig = String::new();
-    let mut iteration = 0u64;
-
-    if !json_output {
-        println!("👁️  Watching wallet: {}...{}", &wallet[..8], &wallet[wallet.len()-8..]);
-        println!("    Polling every {}s — Ctrl+C to stop\n", interval_secs);
+    let mut targets = Vec::new();
+    for arg in args {
+        if arg.ends_with(".json") {
+            for mut target in watch::load_watchlist(arg, rules)? {
+                target.entry.address = sns::resolve_address(&client, &target.entry.address).await?;
+                targets.push(target);
+            }
+        } else if arg.len() > 30 || sns::is_domain(arg) {
+            let address = sns::resolve_address(&client, arg).await?;
+            targets.push(watch::WatchTarget {
+                entry: portfolio::PortfolioEntry { label: watch_label(arg, &address), address, tags: vec![] },
+                rules: rules.clone(),
+            });
+        }
     }
+    if targets.is_empty() {
+        return Err("usage: solscan watch <wallet|name.sol|watchlist.json>...".into());
+    }
+    watch::watch(targets, interval_secs, concurrency, router, json_output).await
+}
 
-    loop {
-        let balance = get_sol_balance(&client, wallet).await.unwrap_or(-1.0);
-        let sigs = get_recent_signatures(&client, wallet, 1).await.unwrap_or_default();
-        let newest_sig = sigs.first().map(|s| s.signature.clone()).unwrap_or_default();
+// === Snapshot Mode ===
 
-        let balance_changed = last_balance >= 0.0 && (balance - last_balance).abs() > 0.000000001;
-        let new_tx = !newest_sig.is_empty() && newest_sig != last_sig && !last_sig.is_empty();
+async fn run_snapshot(args: &[String], json_output: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let positional: Vec<&String> = args.iter().filter(|a| !a.starts_with("--")).collect();
+    let (action, wallet) = match positional.as_slice() {
+        [action, wallet, ..] => (action.as_str(), wallet.as_str()),
+        _ => return Err("usage: solscan snapshot <save|list|diff> <wallet>".into()),
+    };
+    let client = reqwest::Client::new();
+    let wallet = &sns::resolve_address(&client, wallet).await?;
 
-        if iteration == 0 || balance_changed || new_tx {
-            let now = chrono::Local::now().format("%H:%M:%S").to_string();
+    match action {
+        "save" => {
+            let snap = snapshot::capture(&client, wallet).await?;
+            let path = snapshot::save(&snap)?;
             if json_output {
-                let event = serde_json::json!({
-                    "time": now,
-                    "balance": balance,
-                    "change": if balance_changed { Some(balance - last_balance) } else { None },
-                    "new_tx": if new_tx { Some(&newest_sig) } else { None },
-                });
-                println!("{}", event);
+                println!("{}", serde_json::to_string_pretty(&snap.to_json())?);
             } else {
-                if balance_changed {
-                    let diff = balance - last_balance;
-                    let arrow = if diff > 0.0 { "📈" } else { "📉" };
-                    println!("[{}] {} SOL: {:.9} ({}{:.9})",
-                        now, arrow, balance,
-                        if diff > 0.0 { "+" } else { "" }, diff);
-                } else if new_tx {
-                    println!("[{}] 🔔 New TX: {}...{}", now,
-                        &newest_sig[..16], &newest_sig[newest_sig.len()-8..]);
-                } else if iteration == 0 {
-                    println!("[{}] ✅ SOL: {:.9}", now, balance);
-                }
+                println!("📸 Saved snapshot at slot {} → {}", snap.slot, path.display());
+                let stake = match &snap.stake {
+                    Ok(accounts) => format!("{} stake accounts ({:.4} SOL)", accounts.len(), snap.staked_sol().unwrap_or(0.0)),
+                    Err(e) => format!("stake accounts unknown ({})", e),
+                };
+                println!("   {:.6} SOL | {} tokens | {} | {} DeFi positions",
+                    snap.sol_balance, snap.tokens.len(), stake, snap.defi.len());
+            }
+        }
+        "list" => {
+            let snapshots = snapshot::list(wallet)?;
+            if json_output {
+                let json: Vec<_> = snapshots.iter().map(|s| s.to_json()).collect();
+                println!("{}", serde_json::to_string_pretty(&json)?);
+            } else {
+                snapshot::print_list(wallet, &snapshots);
+            }
+        }
+        "diff" => {
+            let from = snapshot::select(wallet, positional.get(2).map_or("latest", |s| s.as_str()))?;
+            let to = match positional.get(3) {
+                Some(selector) => snapshot::select(wallet, selector)?,
+                None => snapshot::capture(&client, wallet).await?,
+            };
+            let diff = snapshot::diff(&from, &to);
+            if json_output {
+                println!("{}", serde_json::to_string_pretty(&snapshot::diff_to_json(&diff))?);
+            } else {
+                snapshot::print_diff(&diff);
             }
         }
+        other => return Err(format!("unknown snapshot action '{}'", other).into()),
+    }
+    Ok(())
+}
 
-        last_balance = balance;
-        if !newest_sig.is_empty() { last_sig = newest_sig; }
-        iteration += 1;
+// === Analyze Mode ===
 
-        tokio::time::sleep(tokio::time::Duration::from_secs(interval_secs)).await;
+async fn run_analyze(primary: &str, others: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let client = reqwest::Client::new();
+    let mut graph = analyze::WalletGraph::new();
+
+    let mut all_wallets = vec![primary.to_string()];
+    for other in others {
+        all_wallets.push(sns::resolve_address(&client, other).await?);
+    }
+
+    for wallet in &all_wallets {
+        eprint!("  Scanning {}...{} ", &wallet[..8], &wallet[wallet.len()-4..]);
+        let balance = get_sol_balance(&client, wallet).await.unwrap_or(0.0);
+        let tokens = get_token_accounts(&client, wallet).await.unwrap_or_default();
+        let mints: Vec<String> = tokens.iter().map(|t| t.mint.clone()).collect();
+        eprintln!("({:.4} SOL, {} tokens)", balance, mints.len());
+        graph.add_wallet(wallet.clone(), balance, mints);
+        // Rate limit courtesy
+        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
     }
+
+    let domains = sns::primary_domains(&client, &all_wallets).await;
+    analyze::print_analysis(&graph, &domains);
+    Ok(())
 }
 
+// === Watch Mode ===
+
 // === RPC Helpers ===
 
 async fn rpc_call(
@@ -369,7 +910,7 @@ async fn rpc_call(
     });
 
     let resp = client
-        .post(&rpc_url())
+        .post(rpc_url())
         .json(&body)
         .send()
         .await?
@@ -398,61 +939,191 @@ struct TokenAccount {
     decimals: u8,
 }
 
+const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+
+/// A token account as returned by `getTokenAccountsByOwner` with `jsonParsed`
+struct ParsedTokenAccount {
+    pubkey: String,
+    program_id: &'static str,
+    lamports: u64,
+    /// The `parsed.info` object (mint, owner, tokenAmount, delegate, extensions, ...)
+    info: serde_json::Value,
+}
+
+impl ParsedTokenAccount {
+    fn mint(&self) -> &str {
+        self.info["mint"].as_str().unwrap_or("unknown")
+    }
+
+    fn raw_amount(&self) -> u64 {
+        self.info["tokenAmount"]["amount"].as_str().and_then(|a| a.parse().ok()).unwrap_or(0)
+    }
+
+    fn ui_amount(&self) -> f64 {
+        self.info["tokenAmount"]["uiAmount"].as_f64().unwrap_or(0.0)
+    }
+
+    fn ui_amount_string(&self) -> String {
+        self.info["tokenAmount"]["uiAmountString"].as_str().unwrap_or("0").to_string()
+    }
+
+    fn decimals(&self) -> u8 {
+        self.info["tokenAmount"]["decimals"].as_u64().unwrap_or(0) as u8
+    }
+
+    /// Look up a Token-2022 extension state by its `extension` name
+    fn extension(&self, name: &str) -> Option<&serde_json::Value> {
+        self.info["extensions"].as_array()?
+            .iter()
+            .find(|e| e["extension"].as_str() == Some(name))
+            .map(|e| &e["state"])
+    }
+}
+
+/// Every SPL Token and Token-2022 account owned by `wallet`, including empty ones
+async fn get_parsed_token_accounts(
+    client: &reqwest::Client,
+    wallet: &str,
+) -> Result<Vec<ParsedTokenAccount>, Box<dyn std::error::Error>> {
+    let mut accounts = Vec::new();
+
+    for program_id in [TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID] {
+        let result = rpc_call(
+            client,
+            "getTokenAccountsByOwner",
+            serde_json::json!([
+                wallet,
+                { "programId": program_id },
+                { "encoding": "jsonParsed" }
+            ]),
+        )
+        .await?;
+
+        if let Some(values) = result["value"].as_array() {
+            for val in values {
+                accounts.push(ParsedTokenAccount {
+                    pubkey: val["pubkey"].as_str().unwrap_or("").to_string(),
+                    program_id,
+                    lamports: val["account"]["lamports"].as_u64().unwrap_or(0),
+                    info: val["account"]["data"]["parsed"]["info"].clone(),
+                });
+            }
+        }
+    }
+
+    Ok(accounts)
+}
+
 async fn get_token_accounts(
     client: &reqwest::Client,
     wallet: &str,
 ) -> Result<Vec<TokenAccount>, Box<dyn std::error::Error>> {
-    let result = rpc_call(
-        client,
-        "getTokenAccountsByOwner",
-        serde_json::json!([
-            wallet,
-            { "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA" },
-            { "encoding": "jsonParsed" }
-        ]),
-    )
-    .await?;
+    let accounts = get_parsed_token_accounts(client, wallet).await?;
+
+    // Skip zero balances
+    Ok(accounts.iter()
+        .filter(|a| a.ui_amount() > 0.0)
+        .map(|a| TokenAccount {
+            mint: a.mint().to_string(),
+            ui_amount: a.ui_amount_string(),
+            decimals: a.decimals(),
+        })
+        .collect())
+}
+
+const STAKE_PROGRAM_ID: &str = "Stake11111111111111111111111111111111111111";
+
+struct StakeAccount {
+    address: String,
+    lamports: u64,
+    /// "initialized", "delegated", ...
+    state: String,
+    voter: Option<String>,
+    deactivating: bool,
+}
+
+/// Stake accounts where `wallet` is the staker or withdrawer
+async fn get_stake_accounts(
+    client: &reqwest::Client,
+    wallet: &str,
+) -> Result<Vec<StakeAccount>, Box<dyn std::error::Error>> {
+    let mut accounts: Vec<StakeAccount> = Vec::new();
+
+    // Meta layout: [4 state][8 rent_exempt_reserve][32 staker][32 withdrawer]
+    for offset in [12, 44] {
+        let result = rpc_call(
+            client,
+            "getProgramAccounts",
+            serde_json::json!([
+                STAKE_PROGRAM_ID,
+                { "encoding": "jsonParsed", "filters": [{ "memcmp": { "offset": offset, "bytes": wallet } }] }
+            ]),
+        )
+        .await?;
+
+        for val in result.as_array().into_iter().flatten() {
+            let address = val["pubkey"].as_str().unwrap_or("").to_string();
+            if accounts.iter().any(|a| a.address == address) { continue; }
+            let parsed = &val["account"]["data"]["parsed"];
+            let delegation = &parsed["info"]["stake"]["delegation"];
+            accounts.push(StakeAccount {
+                address,
+                lamports: val["account"]["lamports"].as_u64().unwrap_or(0),
+                state: parsed["type"].as_str().unwrap_or("unknown").to_string(),
+                voter: delegation["voter"].as_str().map(|v| v.to_string()),
+                deactivating: delegation["deactivationEpoch"].as_str()
+                    .is_some_and(|e| e != u64::MAX.to_string()),
+            });
+        }
+    }
 
-    // Also check Token-2022
-    let result_2022 = rpc_call(
+    Ok(accounts)
+}
+
+async fn get_slot(client: &reqwest::Client) -> Result<u64, Box<dyn std::error::Error>> {
+    let result = rpc_call(client, "getSlot", serde_json::json!([{"commitment": "confirmed"}])).await?;
+    result.as_u64().ok_or_else(|| "no slot in response".into())
+}
+
+async fn get_latest_blockhash(
+    client: &reqwest::Client,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let result = rpc_call(client, "getLatestBlockhash", serde_json::json!([{"commitment": "finalized"}])).await?;
+    result["value"]["blockhash"]
+        .as_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| "no blockhash in response".into())
+}
+
+/// Full transaction with `jsonParsed` instructions (None if the node has pruned it)
+async fn get_transaction(
+    client: &reqwest::Client,
+    signature: &str,
+) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error>> {
+    let result = rpc_call(
         client,
-        "getTokenAccountsByOwner",
-        serde_json::json!([
-            wallet,
-            { "programId": "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb" },
-            { "encoding": "jsonParsed" }
-        ]),
+        "getTransaction",
+        serde_json::json!([signature, { "encoding": "jsonParsed", "maxSupportedTransactionVersion": 0 }]),
     )
     .await?;
+    Ok(if result.is_null() { None } else { Some(result) })
+}
 
-    let mut accounts = Vec::new();
-
-    for result_set in [&result, &result_2022] {
-        if let Some(values) = result_set["value"].as_array() {
-            for val in values {
-                let info = &val["account"]["data"]["parsed"]["info"];
-                let mint = info["mint"].as_str().unwrap_or("unknown").to_string();
-                let token_amount = &info["tokenAmount"];
-                let ui_str = token_amount["uiAmountString"]
-                    .as_str()
-                    .unwrap_or("0")
-                    .to_string();
-                let decimals = token_amount["decimals"].as_u64().unwrap_or(0) as u8;
-
-                // Skip zero balances
-                let amount = token_amount["uiAmount"].as_f64().unwrap_or(0.0);
-                if amount > 0.0 {
-                    accounts.push(TokenAccount {
-                        mint,
-                        ui_amount: ui_str,
-                        decimals,
-                    });
-                }
+/// Top-level and inner (CPI) instructions of a parsed transaction, in execution order
+fn parsed_instructions(tx: &serde_json::Value) -> Vec<&serde_json::Value> {
+    let mut out = Vec::new();
+    let inner = tx["meta"]["innerInstructions"].as_array();
+    if let Some(top) = tx["transaction"]["message"]["instructions"].as_array() {
+        for (i, ix) in top.iter().enumerate() {
+            out.push(ix);
+            let cpis = inner.and_then(|sets| sets.iter().find(|s| s["index"].as_u64() == Some(i as u64)));
+            if let Some(cpis) = cpis.and_then(|s| s["instructions"].as_array()) {
+                out.extend(cpis.iter());
             }
         }
     }
-
-    Ok(accounts)
+    out
 }
 
 struct SignatureInfo {
@@ -480,10 +1151,23 @@ async fn get_recent_signatures(
     wallet: &str,
     limit: usize,
 ) -> Result<Vec<SignatureInfo>, Box<dyn std::error::Error>> {
+    get_signatures_page(client, wallet, limit, None, None).await
+}
+
+async fn get_signatures_page(
+    client: &reqwest::Client,
+    address: &str,
+    limit: usize,
+    before: Option<&str>,
+    until: Option<&str>,
+) -> Result<Vec<SignatureInfo>, Box<dyn std::error::Error>> {
+    let mut config = serde_json::json!({ "limit": limit });
+    if let Some(before) = before { config["before"] = serde_json::json!(before); }
+    if let Some(until) = until { config["until"] = serde_json::json!(until); }
     let result = rpc_call(
         client,
         "getSignaturesForAddress",
-        serde_json::json!([wallet, { "limit": limit }]),
+        serde_json::json!([address, config]),
     )
     .await?;
 
@@ -501,3 +1185,24 @@ async fn get_recent_signatures(
 
     Ok(sigs)
 }
+
+/// Page backwards through an address's signatures (newest first), stopping at `max` or `until`
+async fn get_signature_history(
+    client: &reqwest::Client,
+    address: &str,
+    max: usize,
+    until: Option<&str>,
+) -> Result<Vec<SignatureInfo>, Box<dyn std::error::Error>> {
+    const PAGE: usize = 1000;
+    let mut sigs: Vec<SignatureInfo> = Vec::new();
+    loop {
+        let before = sigs.last().map(|s| s.signature.clone());
+        let limit = PAGE.min(max - sigs.len());
+        let page = get_signatures_page(client, address, limit, before.as_deref(), until).await?;
+        let done = page.len() < limit;
+        sigs.extend(page);
+        if done || sigs.len() >= max {
+            return Ok(sigs);
+        }
+    }
+}
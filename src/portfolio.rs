@@ -0,0 +1,222 @@
+use crate::snapshot::Snapshot;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+/// A labelled wallet from a portfolio / watchlist file
+#[derive(Clone, Debug)]
+pub struct PortfolioEntry {
+    pub address: String,
+    pub label: String,
+    pub tags: Vec<String>,
+}
+
+/// Load a portfolio file:
+/// `{"wallets": [{"address": "...", "label": "Main treasury", "tags": ["treasury"]}]}`
+pub fn load(path: &str) -> Result<Vec<PortfolioEntry>, Box<dyn std::error::Error>> {
+    read_wallets(path)?.iter().enumerate()
+        .map(|(i, w)| parse_entry(path, i, w))
+        .collect()
+}
+
+/// The raw `wallets` array, for formats that extend the portfolio file (e.g. watchlists)
+pub fn read_wallets(path: &str) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error>> {
+    let mut value: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)
+        .map_err(|e| format!("{}: {}", path, e))?;
+    match value["wallets"].take() {
+        serde_json::Value::Array(wallets) => Ok(wallets),
+        _ => Err(format!("{}: expected a \"wallets\" array", path).into()),
+    }
+}
+
+pub fn parse_entry(path: &str, index: usize, w: &serde_json::Value) -> Result<PortfolioEntry, Box<dyn std::error::Error>> {
+    let address = w["address"].as_str()
+        .ok_or_else(|| format!("{}: wallet #{} has no address", path, index + 1))?;
+    Ok(PortfolioEntry {
+        address: address.to_string(),
+        label: w["label"].as_str().unwrap_or(address).to_string(),
+        tags: w["tags"].as_array().into_iter().flatten()
+            .filter_map(|t| t.as_str().map(|t| t.to_string()))
+            .collect(),
+    })
+}
+
+/// Resolve any .sol names in the entries to addresses
+pub async fn resolve_entries(client: &reqwest::Client, entries: &mut [PortfolioEntry]) -> Result<(), Box<dyn std::error::Error>> {
+    for entry in entries.iter_mut() {
+        entry.address = crate::sns::resolve_address(client, &entry.address).await?;
+    }
+    Ok(())
+}
+
+pub struct WalletResult {
+    pub entry: PortfolioEntry,
+    pub snapshot: Result<Snapshot, String>,
+}
+
+/// Scan every wallet, at most `concurrency` at a time; results keep file order
+pub async fn scan(entries: &[PortfolioEntry], concurrency: usize) -> Vec<WalletResult> {
+    let client = reqwest::Client::new();
+    let permits = Arc::new(tokio::sync::Semaphore::new(concurrency.max(1)));
+    let mut tasks = tokio::task::JoinSet::new();
+
+    for (i, entry) in entries.iter().enumerate() {
+        let client = client.clone();
+        let permits = permits.clone();
+        let address = entry.address.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire().await;
+            let snapshot = crate::snapshot::capture(&client, &address).await.map_err(|e| e.to_string());
+            (i, snapshot)
+        });
+    }
+
+    let mut results: Vec<Option<Result<Snapshot, String>>> = (0..entries.len()).map(|_| None).collect();
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((i, snapshot)) = joined {
+            eprintln!("  {} {}", if snapshot.is_ok() { "✅" } else { "❌" }, entries[i].label);
+            results[i] = Some(snapshot);
+        }
+    }
+
+    entries.iter().cloned().zip(results)
+        .map(|(entry, snapshot)| WalletResult {
+            entry,
+            snapshot: snapshot.unwrap_or_else(|| Err("scan task panicked".to_string())),
+        })
+        .collect()
+}
+
+/// Aggregated holdings across a set of wallets
+#[derive(Default)]
+pub struct Totals {
+    pub wallets: usize,
+    pub sol: f64,
+    pub staked_sol: f64,
+    /// Wallets whose stake accounts couldn't be listed, left out of `staked_sol`
+    pub stake_unknown: usize,
+    /// mint -> ui amount
+    pub tokens: BTreeMap<String, f64>,
+    /// protocol -> amount
+    pub defi: BTreeMap<String, f64>,
+}
+
+impl Totals {
+    fn add(&mut self, s: &Snapshot) {
+        self.wallets += 1;
+        self.sol += s.sol_balance;
+        match s.staked_sol() {
+            Some(staked) => self.staked_sol += staked,
+            None => self.stake_unknown += 1,
+        }
+        for (mint, t) in &s.tokens {
+            *self.tokens.entry(mint.clone()).or_default() += t.ui_amount();
+        }
+        for d in &s.defi {
+            *self.defi.entry(d.protocol.clone()).or_default() += d.amount;
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "wallets": self.wallets,
+            "sol": self.sol,
+            "staked_sol": self.staked_sol,
+            "stake_unknown_wallets": self.stake_unknown,
+            "tokens": self.tokens,
+            "defi": self.defi,
+        })
+    }
+}
+
+pub struct PortfolioReport {
+    pub results: Vec<WalletResult>,
+    pub total: Totals,
+    /// tag -> subtotal ("untagged" for wallets without tags)
+    pub groups: BTreeMap<String, Totals>,
+}
+
+pub fn aggregate(results: Vec<WalletResult>) -> PortfolioReport {
+    let mut total = Totals::default();
+    let mut groups: BTreeMap<String, Totals> = BTreeMap::new();
+    // A wallet listed twice (e.g. by address and by .sol name) only counts once per sum
+    let mut counted: HashSet<&str> = HashSet::new();
+    let mut counted_in_group: HashSet<(String, &str)> = HashSet::new();
+    for r in &results {
+        let Ok(snapshot) = &r.snapshot else { continue };
+        let address = r.entry.address.as_str();
+        if counted.insert(address) {
+            total.add(snapshot);
+        }
+        let tags = if r.entry.tags.is_empty() { vec!["untagged".to_string()] } else { r.entry.tags.clone() };
+        for tag in tags {
+            if counted_in_group.insert((tag.clone(), address)) {
+                groups.entry(tag).or_default().add(snapshot);
+            }
+        }
+    }
+    PortfolioReport { results, total, groups }
+}
+
+pub fn to_json(report: &PortfolioReport) -> serde_json::Value {
+    serde_json::json!({
+        "total": report.total.to_json(),
+        "groups": report.groups.iter().map(|(tag, t)| (tag.clone(), t.to_json())).collect::<serde_json::Map<_, _>>(),
+        "wallets": report.results.iter().map(|r| {
+            let mut json = serde_json::json!({
+                "address": r.entry.address,
+                "label": r.entry.label,
+                "tags": r.entry.tags,
+            });
+            match &r.snapshot {
+                Ok(s) => json["snapshot"] = s.to_json(),
+                Err(e) => json["error"] = serde_json::json!(e),
+            }
+            json
+        }).collect::<Vec<_>>(),
+    })
+}
+
+fn print_totals(t: &Totals, indent: &str) {
+    let unknown = match t.stake_unknown {
+        0 => String::new(),
+        n => format!(" (+ unknown for {} wallets)", n),
+    };
+    println!("{}SOL: {:.6} | staked: {:.6}{} | tokens: {} | wallets: {}", indent, t.sol, t.staked_sol, unknown, t.tokens.len(), t.wallets);
+    for (protocol, amount) in &t.defi {
+        println!("{}  🏦 {}: {}", indent, protocol, amount);
+    }
+
+    let mut top: Vec<(&String, &f64)> = t.tokens.iter().collect();
+    top.sort_by(|a, b| b.1.total_cmp(a.1));
+    if !top.is_empty() {
+        println!("{}📦 Largest token positions (by units):", indent);
+        for (mint, amount) in top.iter().take(10) {
+            println!("{}  {}: {}", indent, crate::short(mint), amount);
+        }
+    }
+}
+
+pub fn print_report(report: &PortfolioReport) {
+    println!("\n💼 Portfolio — {} wallets", report.results.len());
+    println!("══════════════════════════════════════════════════════════");
+    println!("  {:24} {:14} {:>14} {:>12} {:>6}", "Label", "Address", "SOL", "Staked", "Tokens");
+    println!("  {:24} {:14} {:>14} {:>12} {:>6}", "─────", "───────", "───", "──────", "──────");
+    for r in &report.results {
+        let addr = &r.entry.address;
+        let short = format!("{}...{}", &addr[..6.min(addr.len())], &addr[addr.len().saturating_sub(4)..]);
+        match &r.snapshot {
+            Ok(s) => println!("  {:24} {:14} {:>14.6} {:>12} {:>6}",
+                r.entry.label, short, s.sol_balance, s.staked_sol().map_or("?".to_string(), |x| format!("{:.4}", x)), s.tokens.len()),
+            Err(e) => println!("  {:24} {:14} ❌ {}", r.entry.label, short, e),
+        }
+    }
+
+    println!("\n  🏷️  Groups:");
+    for (tag, t) in &report.groups {
+        println!("    [{}]", tag);
+        print_totals(t, "      ");
+    }
+
+    println!("\n  📊 Total:");
+    print_totals(&report.total, "    ");
+}
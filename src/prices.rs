@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+/// Native SOL; wrapped SOL balances are folded into it
+pub const SOL: &str = "SOL";
+pub const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// A source of USD prices. Sources are loaded up front so lookups stay synchronous.
+pub trait PriceSource {
+    /// USD price of `asset` (a mint, or "SOL") at unix time `ts`, if known
+    fn price_at(&self, asset: &str, ts: i64) -> Option<f64>;
+}
+
+/// Tries each source in order, first hit wins
+pub struct PriceChain {
+    sources: Vec<Box<dyn PriceSource>>,
+}
+
+impl PriceChain {
+    pub fn new() -> Self {
+        Self { sources: Vec::new() }
+    }
+
+    pub fn with(mut self, source: impl PriceSource + 'static) -> Self {
+        self.sources.push(Box::new(source));
+        self
+    }
+}
+
+impl PriceSource for PriceChain {
+    fn price_at(&self, asset: &str, ts: i64) -> Option<f64> {
+        self.sources.iter().find_map(|s| s.price_at(asset, ts))
+    }
+}
+
+/// Historical prices from a CSV file: `asset,date,usd` where date is YYYY-MM-DD or unix seconds.
+/// A lookup returns the latest price at or before `ts`.
+pub struct CsvPrices {
+    /// asset -> (ts, price) sorted by ts
+    series: HashMap<String, Vec<(i64, f64)>>,
+}
+
+impl CsvPrices {
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = std::fs::read_to_string(path)?;
+        let mut series: HashMap<String, Vec<(i64, f64)>> = HashMap::new();
+        for (n, line) in text.lines().enumerate() {
+            let cols: Vec<&str> = line.split(',').map(|c| c.trim()).collect();
+            if cols.len() < 3 || line.starts_with('#') { continue; }
+            let ts = match parse_time(cols[1]) {
+                Some(ts) => ts,
+                None if n == 0 => continue, // header row
+                None => return Err(format!("{}:{}: bad date '{}'", path, n + 1, cols[1]).into()),
+            };
+            let price: f64 = cols[2].parse().map_err(|_| format!("{}:{}: bad price '{}'", path, n + 1, cols[2]))?;
+            let asset = if cols[0] == WSOL_MINT { SOL } else { cols[0] };
+            series.entry(asset.to_string()).or_default().push((ts, price));
+        }
+        for points in series.values_mut() {
+            points.sort_by_key(|(ts, _)| *ts);
+        }
+        Ok(Self { series })
+    }
+}
+
+impl PriceSource for CsvPrices {
+    fn price_at(&self, asset: &str, ts: i64) -> Option<f64> {
+        let points = self.series.get(asset)?;
+        let idx = points.partition_point(|(t, _)| *t <= ts);
+        points.get(idx.checked_sub(1)?).map(|(_, p)| *p)
+    }
+}
+
+fn parse_time(s: &str) -> Option<i64> {
+    if let Ok(ts) = s.parse::<i64>() {
+        return Some(ts);
+    }
+    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()?
+        .and_hms_opt(0, 0, 0)
+        .map(|t| t.and_utc().timestamp())
+}
+
+/// USD stablecoins pegged at 1.0
+pub struct Stablecoins;
+
+const STABLECOINS: &[&str] = &[
+    "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", // USDC
+    "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", // USDT
+    "2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo", // PYUSD
+];
+
+impl PriceSource for Stablecoins {
+    fn price_at(&self, asset: &str, _ts: i64) -> Option<f64> {
+        STABLECOINS.contains(&asset).then_some(1.0)
+    }
+}
+
+/// Spot prices from the Jupiter price API. Only answers for timestamps in the last day,
+/// so it values open positions without pretending to know historical prices.
+pub struct JupiterPrices {
+    spot: HashMap<String, f64>,
+    fetched_at: i64,
+}
+
+const SPOT_WINDOW_SECS: i64 = 86_400;
+
+impl JupiterPrices {
+    pub async fn fetch(assets: &[String]) -> Result<Self, Box<dyn std::error::Error>> {
+        let base = std::env::var("JUPITER_PRICE_URL")
+            .unwrap_or_else(|_| "https://lite-api.jup.ag/price/v3".to_string());
+        let client = reqwest::Client::new();
+        let mut spot = HashMap::new();
+
+        let ids: Vec<&str> = assets.iter()
+            .map(|a| if a == SOL { WSOL_MINT } else { a.as_str() })
+            .collect();
+        // The API accepts up to 50 ids per request
+        for chunk in ids.chunks(50) {
+            let resp: serde_json::Value = client.get(&base)
+                .query(&[("ids", chunk.join(","))])
+                .send().await?
+                .json().await?;
+            for id in chunk {
+                if let Some(price) = resp[*id]["usdPrice"].as_f64() {
+                    let asset = if *id == WSOL_MINT { SOL } else { id };
+                    spot.insert(asset.to_string(), price);
+                }
+            }
+        }
+
+        Ok(Self { spot, fetched_at: chrono::Utc::now().timestamp() })
+    }
+}
+
+impl PriceSource for JupiterPrices {
+    fn price_at(&self, asset: &str, ts: i64) -> Option<f64> {
+        if (self.fetched_at - ts).abs() > SPOT_WINDOW_SECS {
+            return None;
+        }
+        self.spot.get(asset).copied()
+    }
+}
@@ -0,0 +1,459 @@
+use crate::authority::UPGRADEABLE_LOADER;
+use crate::crypto::find_program_address;
+use crate::encoding::{base64_decode, bs58_encode, pubkey_bytes};
+use crate::governance::{anchor_discriminator, get_account, Reader, GOVERNANCE_PROGRAMS, SQUADS_V3, SQUADS_V4};
+use serde_json::Value;
+
+/// Most recent multisig transactions inspected each check
+const RECENT_TRANSACTIONS: u64 = 20;
+
+/// Where a proposal is in its life
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stage {
+    /// Drafted or being voted on
+    Pending,
+    /// Enough approvals; executable once any timelock passes
+    Approved,
+    Executed,
+    /// Rejected, cancelled, defeated or vetoed
+    Closed,
+}
+
+impl Stage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Pending => "pending",
+            Stage::Approved => "approved",
+            Stage::Executed => "executed",
+            Stage::Closed => "closed",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        [Stage::Pending, Stage::Approved, Stage::Executed, Stage::Closed].into_iter().find(|s| s.name() == name)
+    }
+}
+
+/// A multisig transaction or DAO proposal that upgrades, re-keys or closes a program
+#[derive(Clone, Debug)]
+pub struct Proposal {
+    /// Proposal account (Squads v4, SPL Governance) or transaction account (Squads v3)
+    pub address: String,
+    pub label: String,
+    /// Transaction index in a Squads multisig; None for DAO proposals
+    pub index: Option<u64>,
+    pub stage: Stage,
+    /// What it does to the program, e.g. "Upgrade from buffer ..."
+    pub actions: Vec<String>,
+    pub approvals: Option<u32>,
+    /// When the timelock after approval runs out
+    pub executable_at: Option<i64>,
+}
+
+impl Proposal {
+    /// "Squads v4 transaction #12: Upgrade from buffer ... (2 approvals, executable from ...)"
+    pub fn describe(&self) -> String {
+        let mut notes = Vec::new();
+        if let Some(approvals) = self.approvals {
+            notes.push(format!("{} approvals", approvals));
+        }
+        if let Some(at) = self.executable_at.and_then(|t| chrono::DateTime::from_timestamp(t, 0)) {
+            notes.push(format!("executable from {}", at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")));
+        }
+        format!(
+            "{}: {}{}",
+            self.label,
+            self.actions.join("; "),
+            if notes.is_empty() { String::new() } else { format!(" ({})", notes.join(", ")) }
+        )
+    }
+
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({
+            "address": self.address,
+            "label": self.label,
+            "index": self.index,
+            "stage": self.stage.name(),
+            "actions": self.actions,
+            "approvals": self.approvals,
+            "executable_at": self.executable_at,
+        })
+    }
+
+    pub fn from_json(json: &Value) -> Option<Self> {
+        let label = json["label"].as_str()?.to_string();
+        Some(Self {
+            address: json["address"].as_str()?.to_string(),
+            // State saved before this field existed only has the index in the label
+            index: json["index"].as_u64()
+                .or_else(|| label.rsplit_once(" #").and_then(|(_, i)| i.parse().ok())),
+            label,
+            stage: Stage::parse(json["stage"].as_str()?)?,
+            actions: json["actions"].as_array().into_iter().flatten()
+                .filter_map(|a| a.as_str().map(|a| a.to_string()))
+                .collect(),
+            approvals: json["approvals"].as_u64().map(|a| a as u32),
+            executable_at: json["executable_at"].as_i64(),
+        })
+    }
+}
+
+/// The program whose upgrade proposals we're looking for
+pub struct Target<'a> {
+    pub program_id: &'a str,
+    pub programdata: Option<&'a str>,
+}
+
+impl Target<'_> {
+    /// Describe a BPF Upgradeable Loader instruction if it acts on this program
+    fn loader_action(&self, program: &str, accounts: &[String], data: &[u8]) -> Option<String> {
+        if program != UPGRADEABLE_LOADER {
+            return None;
+        }
+        let account = |i: usize| accounts.get(i).map(|a| a.as_str());
+        let on_programdata = self.programdata.is_some() && account(0) == self.programdata;
+        match u32::from_le_bytes(data.get(..4)?.try_into().ok()?) {
+            // Upgrade: [programdata, program, buffer, spill, rent, clock, authority]
+            3 if account(1) == Some(self.program_id) => Some(format!("Upgrade from buffer {}", account(2).unwrap_or("?"))),
+            // SetAuthority / SetAuthorityChecked: [account, current authority, new authority]
+            4 | 7 if on_programdata => Some(format!("SetAuthority → {}", account(2).unwrap_or("none (immutable)"))),
+            // Close: [account, recipient, authority, program]
+            5 if on_programdata => Some("Close program".to_string()),
+            _ => None,
+        }
+    }
+}
+
+async fn get_multiple_accounts(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    addresses: &[String],
+) -> Result<Vec<Option<Vec<u8>>>, Box<dyn std::error::Error>> {
+    let mut out = Vec::new();
+    for chunk in addresses.chunks(100) {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0", "id": 1,
+            "method": "getMultipleAccounts",
+            "params": [chunk, { "encoding": "base64" }]
+        });
+        let resp: Value = client.post(rpc_url).json(&body).send().await?.json().await?;
+        let values = resp["result"]["value"].as_array().ok_or("getMultipleAccounts: no result")?;
+        for value in values {
+            let data = value["data"].as_array().and_then(|d| d.first()).and_then(|v| v.as_str());
+            out.push(match data {
+                Some(b64) => Some(base64_decode(b64)?),
+                None => None,
+            });
+        }
+    }
+    Ok(out)
+}
+
+async fn get_program_accounts(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    program: &str,
+    filters: Value,
+    data_slice: Option<(usize, usize)>,
+) -> Result<Vec<(String, Vec<u8>)>, Box<dyn std::error::Error>> {
+    let mut config = serde_json::json!({ "encoding": "base64", "filters": filters });
+    if let Some((offset, length)) = data_slice {
+        config["dataSlice"] = serde_json::json!({ "offset": offset, "length": length });
+    }
+    let body = serde_json::json!({
+        "jsonrpc": "2.0", "id": 1,
+        "method": "getProgramAccounts",
+        "params": [program, config]
+    });
+    let resp: Value = client.post(rpc_url).json(&body).send().await?.json().await?;
+    if let Some(err) = resp.get("error") {
+        return Err(format!("getProgramAccounts: {}", err["message"].as_str().unwrap_or("RPC error")).into());
+    }
+    let mut out = Vec::new();
+    for acc in resp["result"].as_array().into_iter().flatten() {
+        let b64 = acc["account"]["data"].as_array().and_then(|d| d.first()).and_then(|v| v.as_str()).unwrap_or("");
+        out.push((acc["pubkey"].as_str().unwrap_or("").to_string(), base64_decode(b64)?));
+    }
+    Ok(out)
+}
+
+/// Proposals from the multisig or governance `controller` that touch `target`. `known` are
+/// the proposals from the previous check; finished DAO proposals are only followed if known,
+/// and unfinished multisig transactions are followed however old they get.
+pub async fn find(
+    rpc_url: &str,
+    controller: &str,
+    target: &Target<'_>,
+    known: &[Proposal],
+) -> Result<Vec<Proposal>, Box<dyn std::error::Error>> {
+    let client = reqwest::Client::new();
+    let Some((owner, data)) = get_account(&client, rpc_url, controller).await? else {
+        return Ok(Vec::new());
+    };
+    match owner.as_str() {
+        SQUADS_V4 => squads_v4(&client, rpc_url, controller, &data, target, known).await,
+        SQUADS_V3 => squads_v3(&client, rpc_url, controller, &data, target, known).await,
+        o if GOVERNANCE_PROGRAMS.contains(&o) => governance(&client, rpc_url, o, controller, target, known).await,
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// The most recent transaction indexes, plus older ones from `known` that haven't finished
+fn followed_indexes(latest: u64, known: &[Proposal]) -> Vec<u64> {
+    let recent = latest.saturating_sub(RECENT_TRANSACTIONS - 1).max(1);
+    let mut indexes: Vec<u64> = known.iter()
+        .filter(|p| matches!(p.stage, Stage::Pending | Stage::Approved))
+        .filter_map(|p| p.index)
+        .filter(|i| *i < recent)
+        .collect();
+    indexes.sort_unstable();
+    indexes.dedup();
+    indexes.extend(recent..=latest);
+    indexes
+}
+
+async fn squads_v4(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    multisig: &str,
+    data: &[u8],
+    target: &Target<'_>,
+    known: &[Proposal],
+) -> Result<Vec<Proposal>, Box<dyn std::error::Error>> {
+    // Multisig: discriminator, create_key, config_authority, threshold u16, time_lock u32, transaction_index u64
+    let mut r = Reader::new(data, 8 + 32 + 32 + 2);
+    let time_lock = r.u32().ok_or("truncated Squads multisig")?;
+    let latest = r.u64().ok_or("truncated Squads multisig")?;
+
+    let program = pubkey_bytes(SQUADS_V4)?;
+    let ms = pubkey_bytes(multisig)?;
+    let indexes = followed_indexes(latest, known);
+    let mut addresses = Vec::new();
+    for index in &indexes {
+        let index = index.to_le_bytes();
+        addresses.push(bs58_encode(&find_program_address(&[b"multisig", &ms, b"transaction", &index], &program).0));
+        addresses.push(bs58_encode(&find_program_address(&[b"multisig", &ms, b"transaction", &index, b"proposal"], &program).0));
+    }
+    let accounts = get_multiple_accounts(client, rpc_url, &addresses).await?;
+
+    let mut proposals = Vec::new();
+    for (i, index) in indexes.iter().enumerate() {
+        let Some(actions) = accounts[2 * i].as_deref().and_then(|tx| v4_actions(tx, target)) else { continue };
+        if actions.is_empty() {
+            continue;
+        }
+        let (stage, approvals, approved_at) = accounts[2 * i + 1].as_deref()
+            .and_then(v4_proposal)
+            .unwrap_or((Stage::Pending, 0, None));
+        proposals.push(Proposal {
+            address: addresses[2 * i + 1].clone(),
+            label: format!("Squads v4 transaction #{}", index),
+            index: Some(*index),
+            stage,
+            actions,
+            approvals: Some(approvals),
+            executable_at: approved_at.filter(|_| stage == Stage::Approved).map(|t| t + time_lock as i64),
+        });
+    }
+    Ok(proposals)
+}
+
+/// Loader actions in a `VaultTransaction`: multisig, creator, index u64, bump, vault_index,
+/// vault_bump, ephemeral_signer_bumps Vec<u8>, then the message
+fn v4_actions(data: &[u8], target: &Target<'_>) -> Option<Vec<String>> {
+    if data.get(..8)? != anchor_discriminator("VaultTransaction") {
+        return None;
+    }
+    let mut r = Reader::new(data, 8 + 32 + 32 + 8 + 3);
+    r.byte_vec()?;
+    // num_signers, num_writable_signers, num_writable_non_signers
+    r.bytes(3)?;
+    let keys = (0..r.u32()?).map(|_| r.pubkey()).collect::<Option<Vec<_>>>()?;
+    let mut actions = Vec::new();
+    for _ in 0..r.u32()? {
+        let program = keys.get(r.u8()? as usize).cloned().unwrap_or_default();
+        // Indexes past the static keys point into lookup tables, which we don't resolve
+        let accounts: Vec<String> = r.byte_vec()?.iter()
+            .map(|i| keys.get(*i as usize).cloned().unwrap_or_else(|| "?".to_string()))
+            .collect();
+        let data = r.byte_vec()?;
+        actions.extend(target.loader_action(&program, &accounts, data));
+    }
+    Some(actions)
+}
+
+/// `Proposal`: multisig, transaction_index u64, status (enum, timestamp on all but Executing),
+/// bump, approved Vec<Pubkey>
+fn v4_proposal(data: &[u8]) -> Option<(Stage, u32, Option<i64>)> {
+    if data.get(..8)? != anchor_discriminator("Proposal") {
+        return None;
+    }
+    let mut r = Reader::new(data, 8 + 32 + 8);
+    let status = r.u8()?;
+    let timestamp = if status == 4 { None } else { Some(r.u64()? as i64) };
+    r.u8()?;
+    let approvals = r.u32()?;
+    let stage = match status {
+        0 | 1 => Stage::Pending,
+        3 => Stage::Approved,
+        4 | 5 => Stage::Executed,
+        _ => Stage::Closed,
+    };
+    Some((stage, approvals, timestamp))
+}
+
+async fn squads_v3(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    multisig: &str,
+    data: &[u8],
+    target: &Target<'_>,
+    known: &[Proposal],
+) -> Result<Vec<Proposal>, Box<dyn std::error::Error>> {
+    // Ms: discriminator, threshold u16, authority_index u16, transaction_index u32
+    let latest = Reader::new(data, 8 + 2 + 2).u32().ok_or("truncated Squads multisig")?;
+    let program = pubkey_bytes(SQUADS_V3)?;
+    let ms = pubkey_bytes(multisig)?;
+    let indexes = followed_indexes(latest as u64, known);
+    let tx_keys: Vec<[u8; 32]> = indexes.iter()
+        .map(|i| find_program_address(&[b"squad", &ms, &(*i as u32).to_le_bytes(), b"transaction"], &program).0)
+        .collect();
+    let txs = get_multiple_accounts(client, rpc_url, &tx_keys.iter().map(|k| bs58_encode(k)).collect::<Vec<_>>()).await?;
+
+    // MsTransaction: creator, ms, transaction_index u32, authority_index u32, authority_bump,
+    // status, instruction_index, bump, approved Vec<Pubkey>
+    let mut found = Vec::new();
+    let mut ix_keys = Vec::new();
+    for (i, tx) in txs.iter().enumerate() {
+        let Some(tx) = tx.as_deref().filter(|tx| tx.get(..8) == Some(&anchor_discriminator("MsTransaction"))) else { continue };
+        let mut r = Reader::new(tx, 8 + 32 + 32 + 4 + 4 + 1);
+        let (Some(status), Some(ix_count), Some(_), Some(approvals)) = (r.u8(), r.u8(), r.u8(), r.u32()) else { continue };
+        let stage = match status {
+            0 | 1 => Stage::Pending,
+            2 => Stage::Approved,
+            3 => Stage::Executed,
+            _ => Stage::Closed,
+        };
+        let first = ix_keys.len();
+        for ix in 1..=ix_count {
+            ix_keys.push(bs58_encode(&find_program_address(&[b"squad", &tx_keys[i], &[ix], b"instruction"], &program).0));
+        }
+        found.push((i, stage, approvals, first..ix_keys.len()));
+    }
+    let instructions = get_multiple_accounts(client, rpc_url, &ix_keys).await?;
+
+    let mut proposals = Vec::new();
+    for (i, stage, approvals, range) in found {
+        let actions: Vec<String> = instructions[range].iter().flatten()
+            .filter_map(|ix| v3_action(ix, target))
+            .collect();
+        if actions.is_empty() {
+            continue;
+        }
+        proposals.push(Proposal {
+            address: bs58_encode(&tx_keys[i]),
+            label: format!("Squads v3 transaction #{}", indexes[i]),
+            index: Some(indexes[i]),
+            stage,
+            actions,
+            approvals: Some(approvals),
+            executable_at: None,
+        });
+    }
+    Ok(proposals)
+}
+
+/// `MsInstruction`: program_id, keys Vec<{pubkey, is_signer, is_writable}>, data Vec<u8>
+fn v3_action(data: &[u8], target: &Target<'_>) -> Option<String> {
+    if data.get(..8)? != anchor_discriminator("MsInstruction") {
+        return None;
+    }
+    let mut r = Reader::new(data, 8);
+    let program = r.pubkey()?;
+    let accounts = (0..r.u32()?)
+        .map(|_| {
+            let key = r.pubkey()?;
+            r.bytes(2)?;
+            Some(key)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    target.loader_action(&program, &accounts, r.byte_vec()?)
+}
+
+async fn governance(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    program: &str,
+    governance: &str,
+    target: &Target<'_>,
+    known: &[Proposal],
+) -> Result<Vec<Proposal>, Box<dyn std::error::Error>> {
+    // ProposalV2 (account type 14): governance, governing_token_mint, state
+    let filters = serde_json::json!([
+        { "memcmp": { "offset": 0, "bytes": bs58_encode(&[14]) } },
+        { "memcmp": { "offset": 1, "bytes": governance } },
+    ]);
+    let headers = get_program_accounts(client, rpc_url, program, filters, Some((0, 66))).await?;
+
+    let mut proposals = Vec::new();
+    for (address, header) in headers {
+        let Some(state) = header.get(65) else { continue };
+        let stage = match state {
+            0..=2 => Stage::Pending,
+            3 => Stage::Approved,
+            4 | 5 | 8 => Stage::Executed,
+            _ => Stage::Closed,
+        };
+        if let Some(prev) = known.iter().find(|p| p.address == address) {
+            proposals.push(Proposal { stage, ..prev.clone() });
+            continue;
+        }
+        // Proposals that finished before we first saw them are history, not warnings
+        if !matches!(stage, Stage::Pending | Stage::Approved) {
+            continue;
+        }
+
+        // ProposalTransactionV2 (account type 13): proposal, option_index, transaction_index u16,
+        // hold_up_time u32, instructions Vec<{program_id, accounts Vec<meta>, data Vec<u8>}>
+        let filters = serde_json::json!([
+            { "memcmp": { "offset": 0, "bytes": bs58_encode(&[13]) } },
+            { "memcmp": { "offset": 1, "bytes": address } },
+        ]);
+        let mut actions = Vec::new();
+        for (_, tx) in get_program_accounts(client, rpc_url, program, filters, None).await? {
+            let mut r = Reader::new(&tx, 1 + 32 + 1 + 2);
+            let Some(hold_up) = r.u32() else { continue };
+            for _ in 0..r.u32().unwrap_or(0) {
+                let Some(action) = governance_instruction(&mut r, target) else { break };
+                actions.extend(action.map(|a| format!("{} (hold-up {}h)", a, hold_up / 3_600)));
+            }
+        }
+        if actions.is_empty() {
+            continue;
+        }
+        proposals.push(Proposal {
+            address: address.clone(),
+            label: format!("SPL Governance proposal {}", address),
+            index: None,
+            stage,
+            actions,
+            approvals: None,
+            executable_at: None,
+        });
+    }
+    Ok(proposals)
+}
+
+/// One `InstructionData`; the outer None means the data ran out
+fn governance_instruction(r: &mut Reader, target: &Target<'_>) -> Option<Option<String>> {
+    let program = r.pubkey()?;
+    let accounts = (0..r.u32()?)
+        .map(|_| {
+            let key = r.pubkey()?;
+            r.bytes(2)?;
+            Some(key)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let data = r.byte_vec()?;
+    Some(target.loader_action(&program, &accounts, data))
+}
@@ -0,0 +1,178 @@
+use crate::tx::{AccountMeta, Instruction};
+use crate::ParsedTokenAccount;
+
+/// SPL Token `CloseAccount` instruction index (same for Token-2022)
+const CLOSE_ACCOUNT_IX: u8 = 9;
+/// Close instructions packed into one unsigned transaction (keeps well under the 1232-byte limit)
+const CLOSES_PER_TX: usize = 20;
+
+/// Rent-reclaim report — empty and dust token accounts and the SOL locked in them
+pub struct RentReport {
+    pub wallet: String,
+    pub dust_threshold: f64,
+    pub accounts: Vec<RentAccount>,
+}
+
+pub struct RentAccount {
+    pub address: String,
+    pub mint: String,
+    pub program_id: String,
+    pub ui_amount: f64,
+    pub lamports: u64,
+    pub is_empty: bool,
+    /// Reasons the owner cannot close this account right now
+    pub blockers: Vec<String>,
+}
+
+impl RentAccount {
+    pub fn is_closable(&self) -> bool {
+        self.is_empty && self.blockers.is_empty()
+    }
+}
+
+impl RentReport {
+    pub fn closable(&self) -> impl Iterator<Item = &RentAccount> {
+        self.accounts.iter().filter(|a| a.is_closable())
+    }
+
+    pub fn reclaimable_sol(&self) -> f64 {
+        self.closable().map(|a| a.lamports).sum::<u64>() as f64 / crate::LAMPORTS_PER_SOL
+    }
+
+    /// Rent that becomes reclaimable once dust balances are burned
+    pub fn dust_sol(&self) -> f64 {
+        self.accounts.iter()
+            .filter(|a| !a.is_empty && a.blockers.is_empty())
+            .map(|a| a.lamports)
+            .sum::<u64>() as f64 / crate::LAMPORTS_PER_SOL
+    }
+}
+
+pub async fn rent_report(
+    client: &reqwest::Client,
+    wallet: &str,
+    dust_threshold: f64,
+) -> Result<RentReport, Box<dyn std::error::Error>> {
+    let parsed = crate::get_parsed_token_accounts(client, wallet).await?;
+
+    let accounts = parsed.iter()
+        .filter(|a| a.raw_amount() == 0 || a.ui_amount() < dust_threshold)
+        .map(|a| RentAccount {
+            address: a.pubkey.clone(),
+            mint: a.mint().to_string(),
+            program_id: a.program_id.to_string(),
+            ui_amount: a.ui_amount(),
+            lamports: a.lamports,
+            is_empty: a.raw_amount() == 0,
+            blockers: close_blockers(a, wallet),
+        })
+        .collect();
+
+    Ok(RentReport { wallet: wallet.to_string(), dust_threshold, accounts })
+}
+
+fn close_blockers(account: &ParsedTokenAccount, wallet: &str) -> Vec<String> {
+    let mut blockers = Vec::new();
+
+    if account.info["state"].as_str() == Some("frozen") {
+        blockers.push("account is frozen".to_string());
+    }
+    if let Some(close_authority) = account.info["closeAuthority"].as_str() {
+        if close_authority != wallet {
+            blockers.push(format!("close authority is {}", close_authority));
+        }
+    }
+    // Token-2022: withheld transfer fees must be harvested to the mint before closing
+    if let Some(fees) = account.extension("transferFeeAmount") {
+        let withheld = fees["withheldAmount"].as_u64().unwrap_or(0);
+        if withheld > 0 {
+            blockers.push(format!("{} withheld transfer-fee units must be harvested", withheld));
+        }
+    }
+
+    blockers
+}
+
+/// Unsigned `CloseAccount` transactions returning rent to the wallet, base64-encoded
+pub async fn close_transactions(
+    client: &reqwest::Client,
+    report: &RentReport,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let blockhash = crate::get_latest_blockhash(client).await?;
+    let closable: Vec<&RentAccount> = report.closable().collect();
+
+    closable.chunks(CLOSES_PER_TX)
+        .map(|batch| {
+            let instructions: Vec<Instruction> = batch.iter()
+                .map(|a| Instruction {
+                    program_id: a.program_id.clone(),
+                    accounts: vec![
+                        AccountMeta::writable(&a.address, false),
+                        AccountMeta::writable(&report.wallet, false),
+                        AccountMeta { pubkey: report.wallet.clone(), is_signer: true, is_writable: false },
+                    ],
+                    data: vec![CLOSE_ACCOUNT_IX],
+                })
+                .collect();
+            crate::tx::unsigned_transaction(&report.wallet, &instructions, &blockhash)
+        })
+        .collect()
+}
+
+pub fn to_json(report: &RentReport) -> serde_json::Value {
+    serde_json::json!({
+        "address": report.wallet,
+        "dust_threshold": report.dust_threshold,
+        "reclaimable_sol": report.reclaimable_sol(),
+        "dust_sol": report.dust_sol(),
+        "accounts": report.accounts.iter().map(|a| serde_json::json!({
+            "address": a.address,
+            "mint": a.mint,
+            "program_id": a.program_id,
+            "balance": a.ui_amount,
+            "rent_sol": a.lamports as f64 / crate::LAMPORTS_PER_SOL,
+            "kind": if a.is_empty { "empty" } else { "dust" },
+            "closable": a.is_closable(),
+            "blockers": a.blockers,
+        })).collect::<Vec<_>>(),
+    })
+}
+
+pub fn print_report(report: &RentReport) {
+    let wallet = &report.wallet;
+    println!("\n🧹 Rent Reclaim Report: {}...{}", &wallet[..8], &wallet[wallet.len()-4..]);
+    println!("═══════════════════════════════════════════");
+
+    if report.accounts.is_empty() {
+        println!("  No empty or dust token accounts found.");
+        return;
+    }
+
+    println!("  {:8} {:>14} {:>10}  {:16} Status", "Kind", "Balance", "Rent SOL", "Mint");
+    println!("  {:8} {:>14} {:>10}  {:16} ──────", "────", "───────", "────────", "────");
+    for a in &report.accounts {
+        let status = if a.is_closable() {
+            "✅ closable".to_string()
+        } else if !a.blockers.is_empty() {
+            format!("⛔ {}", a.blockers.join("; "))
+        } else {
+            "🔥 burn, then close".to_string()
+        };
+        println!("  {:8} {:>14} {:>10.6}  {:15}  {}",
+            if a.is_empty { "empty" } else { "dust" },
+            a.ui_amount,
+            a.lamports as f64 / crate::LAMPORTS_PER_SOL,
+            crate::short(&a.mint),
+            status);
+    }
+
+    let empty = report.accounts.iter().filter(|a| a.is_empty).count();
+    println!("\n  📊 Summary:");
+    println!("    Empty accounts: {}", empty);
+    println!("    Dust accounts (< {}): {}", report.dust_threshold, report.accounts.len() - empty);
+    println!("    Closable now: {}", report.closable().count());
+    println!("    Reclaimable: {:.6} SOL", report.reclaimable_sol());
+    if report.dust_sol() > 0.0 {
+        println!("    + {:.6} SOL after burning dust", report.dust_sol());
+    }
+}
@@ -0,0 +1,221 @@
+use crate::daemon::Severity;
+use std::collections::HashMap;
+
+/// A built-in audit rule. Teams tune or disable rules by id in the rules file.
+pub struct Rule {
+    pub id: &'static str,
+    pub severity: Severity,
+    /// Points added to the risk score; rules that compute their own weight use this as a fallback
+    pub weight: u8,
+    pub title: &'static str,
+}
+
+pub const RULES: &[Rule] = &[
+    Rule { id: "allowlisted", severity: Severity::Info, weight: 0, title: "Program is allowlisted; no other rule applies" },
+    Rule { id: "not_executable", severity: Severity::High, weight: 20, title: "Account is not an executable program" },
+    Rule { id: "upgradeable_hot_key", severity: Severity::High, weight: 30, title: "Upgrade authority is a single key" },
+    Rule { id: "upgradeable_unknown_pda", severity: Severity::High, weight: 25, title: "Upgrade authority is a PDA of an unknown program" },
+    Rule { id: "upgradeable_multisig", severity: Severity::Medium, weight: 15, title: "Upgrade authority is a multisig (weight from threshold and timelock)" },
+    Rule { id: "upgradeable_dao", severity: Severity::Medium, weight: 12, title: "Upgrade authority is a DAO (weight from voting and hold-up time)" },
+    Rule { id: "upgradeable_unknown", severity: Severity::High, weight: 30, title: "Upgrade authority could not be identified" },
+    Rule { id: "immutable", severity: Severity::Info, weight: 0, title: "Upgrade authority revoked or loader v4 program finalized" },
+    Rule { id: "legacy_loader", severity: Severity::Info, weight: 0, title: "Deployed with BPFLoader1/2; the code can never change" },
+    Rule { id: "builtin", severity: Severity::Info, weight: 0, title: "Native builtin; changes only with validator releases" },
+    Rule { id: "retracted", severity: Severity::Medium, weight: 10, title: "Loader v4 program is retracted and can't be invoked" },
+    Rule { id: "closed", severity: Severity::Medium, weight: 10, title: "Program was closed; its code is gone" },
+    Rule { id: "invalid_elf", severity: Severity::High, weight: 20, title: "Deployed code is not a valid sBPF ELF" },
+    Rule { id: "tiny_program", severity: Severity::Medium, weight: 15, title: "Program under 500 bytes, possibly a proxy" },
+    Rule { id: "large_program", severity: Severity::Medium, weight: 10, title: "Program over 500KB, large attack surface" },
+    Rule { id: "cpi", severity: Severity::Info, weight: 0, title: "Makes cross-program invocations" },
+    Rule { id: "return_data", severity: Severity::Info, weight: 0, title: "Returns data to calling programs" },
+    Rule { id: "custom_crypto", severity: Severity::Medium, weight: 5, title: "Calls curve or other crypto syscalls" },
+    Rule { id: "deprecated_syscall", severity: Severity::Medium, weight: 5, title: "Imports deprecated syscalls" },
+    Rule { id: "anchor", severity: Severity::Info, weight: 0, title: "Built with Anchor" },
+    Rule { id: "no_anchor", severity: Severity::Medium, weight: 5, title: "Not built with Anchor" },
+    Rule { id: "no_overflow_checks", severity: Severity::Medium, weight: 5, title: "Built without overflow checks" },
+    Rule { id: "panics", severity: Severity::Info, weight: 0, title: "Contains panic messages" },
+    Rule { id: "hardcoded_programs", severity: Severity::Info, weight: 0, title: "Hard-codes well-known program ids" },
+    Rule { id: "address_strings", severity: Severity::Info, weight: 0, title: "Contains address string literals" },
+    Rule { id: "security_txt", severity: Severity::Info, weight: 0, title: "Embeds a security.txt" },
+    Rule { id: "no_security_txt", severity: Severity::Info, weight: 0, title: "No security.txt" },
+    Rule { id: "no_declared_id", severity: Severity::Info, weight: 0, title: "Own program id not compiled in" },
+];
+
+/// Programs that are allowlisted unless the rules file turns the defaults off
+const DEFAULT_ALLOWLIST: &[&str] = &[
+    "11111111111111111111111111111111",
+    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+    "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+    "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+];
+
+fn rule(id: &str) -> Option<&'static Rule> {
+    RULES.iter().find(|r| r.id == id)
+}
+
+/// A rule that matched, before the rules file is applied
+pub struct Hit {
+    pub rule: &'static str,
+    pub evidence: String,
+    /// Weight the check computed itself, replacing the rule's default
+    pub weight: Option<u8>,
+}
+
+impl Hit {
+    pub fn new(rule: &'static str, evidence: String) -> Self {
+        Self { rule, evidence, weight: None }
+    }
+
+    pub fn weighted(rule: &'static str, weight: u8, evidence: String) -> Self {
+        Self { rule, evidence, weight: Some(weight) }
+    }
+}
+
+/// A rule that fired and what it adds to the risk score
+#[derive(Clone, Debug)]
+pub struct Finding {
+    pub rule: String,
+    pub severity: Severity,
+    pub weight: u8,
+    pub evidence: String,
+}
+
+impl Finding {
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "rule": self.rule,
+            "severity": self.severity.name(),
+            "weight": self.weight,
+            "evidence": self.evidence,
+        })
+    }
+}
+
+/// Risk score from the findings, capped at 100
+pub fn score(findings: &[Finding]) -> u8 {
+    findings.iter().map(|f| f.weight as u32).sum::<u32>().min(100) as u8
+}
+
+#[derive(Clone, Debug)]
+struct Override {
+    enabled: bool,
+    weight: Option<u8>,
+    severity: Option<Severity>,
+    /// Program ids or upgrade authorities the rule doesn't apply to
+    allow: Vec<String>,
+}
+
+/// The built-in rules with a team's tuning applied
+#[derive(Clone, Debug)]
+pub struct RuleSet {
+    overrides: HashMap<String, Override>,
+    allowlist: Vec<String>,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self {
+            overrides: HashMap::new(),
+            allowlist: DEFAULT_ALLOWLIST.iter().map(|id| id.to_string()).collect(),
+        }
+    }
+}
+
+impl RuleSet {
+    /// Load a rules file:
+    /// `{"allowlist": ["<program id>"], "default_allowlist": true,
+    ///   "rules": {"upgradeable_hot_key": {"weight": 40, "severity": "critical"},
+    ///             "no_anchor": {"enabled": false}, "address_strings": {"allow": ["<program or authority>"]}}}`
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| format!("{}: {}", path, e))?;
+        let list = |value: &serde_json::Value| -> Vec<String> {
+            value.as_array().into_iter().flatten()
+                .filter_map(|v| v.as_str().map(|v| v.to_string()))
+                .collect()
+        };
+
+        let mut rules = if json["default_allowlist"].as_bool() == Some(false) {
+            Self { allowlist: Vec::new(), ..Self::default() }
+        } else {
+            Self::default()
+        };
+        rules.allowlist.extend(list(&json["allowlist"]));
+
+        for (id, config) in json["rules"].as_object().into_iter().flatten() {
+            if rule(id).is_none() {
+                return Err(format!("{}: unknown rule '{}' (see `solscan rules`)", path, id).into());
+            }
+            let weight = match config.get("weight") {
+                None => None,
+                Some(w) => Some(w.as_u64().filter(|w| *w <= 100)
+                    .ok_or_else(|| format!("{}: rule '{}': weight must be 0-100", path, id))? as u8),
+            };
+            let severity = match config["severity"].as_str() {
+                Some(name) => Some(name.parse().map_err(|e| format!("{}: rule '{}': {}", path, id, e))?),
+                None => None,
+            };
+            rules.overrides.insert(id.clone(), Override {
+                enabled: config["enabled"].as_bool().unwrap_or(true),
+                weight,
+                severity,
+                allow: list(&config["allow"]),
+            });
+        }
+        Ok(rules)
+    }
+
+    /// `--rules <file>`, else `$SOLSCAN_HOME/rules.json` if present, else the defaults
+    pub fn resolve(path: Option<String>) -> Result<Self, Box<dyn std::error::Error>> {
+        match path {
+            Some(path) => Self::load(&path),
+            None => {
+                let default = crate::data_dir().join("rules.json");
+                if default.exists() {
+                    Self::load(&default.to_string_lossy())
+                } else {
+                    Ok(Self::default())
+                }
+            }
+        }
+    }
+
+    pub fn allowlisted(&self, program_id: &str) -> bool {
+        self.allowlist.iter().any(|id| id == program_id)
+    }
+
+    /// Turn hits into findings: drop disabled and allowed rules, apply weight and severity overrides.
+    /// `subjects` are what a rule's `allow` list is matched against (program id, upgrade authority).
+    pub fn evaluate(&self, subjects: &[&str], hits: Vec<Hit>) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for hit in hits {
+            let Some(rule) = rule(hit.rule) else { continue };
+            let tuning = self.overrides.get(rule.id);
+            if let Some(tuning) = tuning {
+                if !tuning.enabled || tuning.allow.iter().any(|a| subjects.contains(&a.as_str())) {
+                    continue;
+                }
+            }
+            findings.push(Finding {
+                rule: rule.id.to_string(),
+                severity: tuning.and_then(|t| t.severity.clone()).unwrap_or(rule.severity.clone()),
+                weight: tuning.and_then(|t| t.weight).or(hit.weight).unwrap_or(rule.weight),
+                evidence: hit.evidence,
+            });
+        }
+        findings
+    }
+
+    /// Every rule with this set's tuning applied: (rule, enabled, severity, weight)
+    pub fn effective(&self) -> Vec<(&'static Rule, bool, Severity, u8)> {
+        RULES.iter().map(|rule| {
+            let tuning = self.overrides.get(rule.id);
+            (
+                rule,
+                tuning.is_none_or(|t| t.enabled),
+                tuning.and_then(|t| t.severity.clone()).unwrap_or(rule.severity.clone()),
+                tuning.and_then(|t| t.weight).unwrap_or(rule.weight),
+            )
+        }).collect()
+    }
+}
@@ -0,0 +1,485 @@
+use crate::authority::without_padding;
+use crate::encoding::{bs58_decode, pubkey_bytes};
+use crate::governance::{GOVERNANCE_PROGRAMS, SQUADS_V3, SQUADS_V4};
+use crate::rules::Hit;
+use serde_json::Value;
+
+/// Syscalls a program can import. Legacy ELFs name them in `.dynsym`; newer sBPF
+/// versions call them directly by the murmur3 hash of the name.
+const SYSCALLS: &[&str] = &[
+    "abort", "sol_panic_", "sol_log_", "sol_log_64_", "sol_log_compute_units_", "sol_log_pubkey",
+    "sol_log_data", "sol_create_program_address", "sol_try_find_program_address",
+    "sol_sha256", "sol_keccak256", "sol_blake3", "sol_secp256k1_recover", "sol_poseidon",
+    "sol_curve_validate_point", "sol_curve_group_op", "sol_curve_multiscalar_mul", "sol_curve_pairing_map",
+    "sol_alt_bn128_group_op", "sol_alt_bn128_compression", "sol_big_mod_exp",
+    "sol_get_clock_sysvar", "sol_get_epoch_schedule_sysvar", "sol_get_fees_sysvar", "sol_get_rent_sysvar",
+    "sol_get_last_restart_slot", "sol_get_epoch_rewards_sysvar", "sol_get_sysvar", "sol_get_epoch_stake",
+    "sol_memcpy_", "sol_memmove_", "sol_memcmp_", "sol_memset_",
+    "sol_invoke_signed_c", "sol_invoke_signed_rust", "sol_set_return_data", "sol_get_return_data",
+    "sol_get_processed_sibling_instruction", "sol_get_stack_height", "sol_remaining_compute_units",
+    "sol_alloc_free_",
+];
+
+/// Syscalls for elliptic-curve and other cryptography the program does itself
+const CRYPTO_SYSCALLS: &[&str] = &[
+    "sol_secp256k1_recover", "sol_poseidon", "sol_curve_validate_point", "sol_curve_group_op",
+    "sol_curve_multiscalar_mul", "sol_curve_pairing_map", "sol_alt_bn128_group_op",
+    "sol_alt_bn128_compression", "sol_big_mod_exp",
+];
+
+/// Addresses worth naming when a program has them compiled in
+const WELL_KNOWN: &[(&str, &str)] = &[
+    ("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA", "SPL Token"),
+    ("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb", "Token-2022"),
+    ("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL", "Associated Token"),
+    ("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr", "Memo"),
+    ("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s", "Metaplex Token Metadata"),
+    ("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH", "Pyth oracle"),
+    ("SW1TCH7qEPTdLsDHRgPuMQjbQxKdH2aBStViMFnt64f", "Switchboard v2"),
+    ("worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth", "Wormhole"),
+    ("BPFLoaderUpgradeab1e11111111111111111111111", "Upgradeable loader"),
+    ("Stake11111111111111111111111111111111111111", "Stake program"),
+    ("ComputeBudget111111111111111111111111111111", "Compute budget"),
+    ("Ed25519SigVerify111111111111111111111111111", "Ed25519 verify"),
+    ("KeccakSecp256k11111111111111111111111111111", "Secp256k1 verify"),
+    ("Sysvar1nstructions1111111111111111111111111", "Instructions sysvar"),
+    ("SysvarC1ock11111111111111111111111111111111", "Clock sysvar"),
+    ("SysvarRent111111111111111111111111111111111", "Rent sysvar"),
+];
+
+/// Panic messages rustc and std leave behind, by what they reveal
+const PANICS: &[(&str, &str)] = &[
+    ("called `Option::unwrap()` on a `None` value", "unwrap on None"),
+    ("called `Result::unwrap()` on an `Err` value", "unwrap on Err"),
+    ("with overflow", "arithmetic overflow"),
+    ("index out of bounds", "index out of bounds"),
+    ("assertion failed", "assertion"),
+    ("explicit panic", "explicit panic"),
+];
+
+/// What the deployed ELF says about itself
+#[derive(Debug, Clone)]
+pub struct ElfReport {
+    pub size: usize,
+    /// sBPF version from the ELF flags (0 = legacy)
+    pub sbpf_version: u32,
+    pub sections: Vec<(String, u64)>,
+    pub syscalls: Vec<String>,
+    pub anchor: bool,
+    pub anchor_version: Option<String>,
+    /// Well-known programs and sysvars compiled in as 32-byte constants
+    pub known_programs: Vec<(String, String)>,
+    /// Base58 addresses present as string literals
+    pub address_strings: Vec<String>,
+    /// The program's own id is compiled in (`declare_id!`)
+    pub declares_id: bool,
+    pub panics: Vec<(String, usize)>,
+    /// Workspace source files named in panic locations
+    pub source_files: Vec<String>,
+    pub security_txt: Option<SecurityTxt>,
+}
+
+const SECURITY_TXT_BEGIN: &[u8] = b"=======BEGIN SECURITY.TXT V1=======\0";
+const SECURITY_TXT_END: &[u8] = b"=======END SECURITY.TXT V1=======\0";
+
+/// Contact details embedded with the `solana-security-txt` crate: NUL-terminated key/value
+/// pairs (name, project_url, contacts, policy, source_code, auditors, ...) between two markers
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecurityTxt {
+    pub fields: Vec<(String, String)>,
+}
+
+impl SecurityTxt {
+    fn find(elf: &[u8]) -> Option<Self> {
+        let start = elf.windows(SECURITY_TXT_BEGIN.len()).position(|w| w == SECURITY_TXT_BEGIN)? + SECURITY_TXT_BEGIN.len();
+        let len = elf[start..].windows(SECURITY_TXT_END.len()).position(|w| w == SECURITY_TXT_END)?;
+        let parts: Vec<String> = elf[start..start + len].split(|b| *b == 0)
+            .map(|p| String::from_utf8_lossy(p).into_owned())
+            .collect();
+        let fields = parts.chunks_exact(2)
+            .filter(|kv| !kv[0].is_empty())
+            .map(|kv| (kv[0].clone(), kv[1].clone()))
+            .collect();
+        Some(Self { fields })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// "name (contacts)", for one-line reports
+    pub fn summary(&self) -> String {
+        format!("{} ({})", self.get("name").unwrap_or("unnamed"), self.get("contacts").unwrap_or("no contacts"))
+    }
+
+    /// Fields that differ from `before`, as "key: old → new"
+    pub fn changes(&self, before: &SecurityTxt) -> Vec<String> {
+        let mut keys: Vec<&str> = Vec::new();
+        for (key, _) in before.fields.iter().chain(&self.fields) {
+            if !keys.contains(&key.as_str()) {
+                keys.push(key);
+            }
+        }
+        keys.into_iter()
+            .filter(|k| before.get(k) != self.get(k))
+            .map(|k| format!("{}: {} → {}", k, before.get(k).unwrap_or("none"), self.get(k).unwrap_or("none")))
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Value {
+        Value::Object(self.fields.iter().map(|(k, v)| (k.clone(), Value::String(v.clone()))).collect())
+    }
+
+    pub fn from_json(json: &Value) -> Option<Self> {
+        Some(Self {
+            fields: json.as_object()?.iter()
+                .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                .collect(),
+        })
+    }
+}
+
+fn u16_at(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn u64_at(data: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(at..at + 8)?.try_into().ok()?))
+}
+
+/// Length of the file the ELF header describes: the section header table comes last, so it
+/// ends at e_shoff + e_shnum * e_shentsize. None until the header has been written.
+pub fn declared_len(elf: &[u8]) -> Option<usize> {
+    if elf.get(..4) != Some(b"\x7fELF") {
+        return None;
+    }
+    let shoff = u64_at(elf, 40)? as usize;
+    let shentsize = u16_at(elf, 58)? as usize;
+    let shnum = u16_at(elf, 60)? as usize;
+    shoff.checked_add(shnum * shentsize)
+}
+
+struct Section<'a> {
+    name: String,
+    link: usize,
+    data: &'a [u8],
+}
+
+fn sections(elf: &[u8]) -> Result<Vec<Section<'_>>, Box<dyn std::error::Error>> {
+    if elf.get(..4) != Some(b"\x7fELF") {
+        return Err("missing ELF magic".into());
+    }
+    if elf.get(4..6) != Some(&[2, 1]) {
+        return Err("not a 64-bit little-endian ELF".into());
+    }
+    let machine = u16_at(elf, 18).ok_or("truncated ELF header")?;
+    if machine != 247 && machine != 263 {
+        return Err(format!("machine type {} is not BPF", machine).into());
+    }
+    let shoff = u64_at(elf, 40).ok_or("truncated ELF header")? as usize;
+    let shentsize = u16_at(elf, 58).ok_or("truncated ELF header")? as usize;
+    let shnum = u16_at(elf, 60).ok_or("truncated ELF header")? as usize;
+    let shstrndx = u16_at(elf, 62).ok_or("truncated ELF header")? as usize;
+    if shentsize < 64 {
+        return Err("bad section header size".into());
+    }
+
+    let mut raw = Vec::new();
+    for i in 0..shnum {
+        let header = i.checked_mul(shentsize)
+            .and_then(|off| shoff.checked_add(off))
+            .and_then(|at| elf.get(at..at.checked_add(64)?))
+            .ok_or("section headers past end of file")?;
+        let kind = u32_at(header, 4).unwrap_or(0);
+        let offset = u64_at(header, 24).unwrap_or(0) as usize;
+        let size = u64_at(header, 32).unwrap_or(0) as usize;
+        // NOBITS (.bss) occupies no file space
+        let data = if kind == 8 { &[][..] } else { elf.get(offset..offset.saturating_add(size)).ok_or("section past end of file")? };
+        raw.push((u32_at(header, 0).unwrap_or(0) as usize, u32_at(header, 40).unwrap_or(0) as usize, data));
+    }
+    let names = raw.get(shstrndx).map(|s| s.2).unwrap_or(&[]);
+    Ok(raw.into_iter().map(|(name, link, data)| Section { name: c_str(names, name), link, data }).collect())
+}
+
+fn c_str(table: &[u8], at: usize) -> String {
+    let bytes = table.get(at..).unwrap_or(&[]);
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+/// The hash sBPF uses as the immediate of a `call` to a syscall
+fn murmur3_32(data: &[u8]) -> u32 {
+    let mut h: u32 = 0;
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let k = u32::from_le_bytes(chunk.try_into().unwrap()).wrapping_mul(0xcc9e_2d51).rotate_left(15).wrapping_mul(0x1b87_3593);
+        h = (h ^ k).rotate_left(13).wrapping_mul(5).wrapping_add(0xe654_6b64);
+    }
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        let k = tail.iter().rev().fold(0u32, |k, b| (k << 8) | *b as u32);
+        h ^= k.wrapping_mul(0xcc9e_2d51).rotate_left(15).wrapping_mul(0x1b87_3593);
+    }
+    h ^= data.len() as u32;
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x85eb_ca6b);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0xc2b2_ae35);
+    h ^ (h >> 16)
+}
+
+fn syscalls(sections: &[Section<'_>]) -> Vec<String> {
+    let mut found: Vec<String> = Vec::new();
+    // Undefined dynamic symbols (section index 0) are imports
+    for dynsym in sections.iter().filter(|s| s.name == ".dynsym") {
+        let strings = sections.get(dynsym.link).map(|s| s.data).unwrap_or(&[]);
+        for sym in dynsym.data.chunks_exact(24).skip(1) {
+            if u16_at(sym, 6) == Some(0) {
+                let name = c_str(strings, u32_at(sym, 0).unwrap_or(0) as usize);
+                if !name.is_empty() {
+                    found.push(name);
+                }
+            }
+        }
+    }
+    // Static syscalls: `call imm` with src register 0
+    let hashes: Vec<(u32, &str)> = SYSCALLS.iter().map(|s| (murmur3_32(s.as_bytes()), *s)).collect();
+    for text in sections.iter().filter(|s| s.name == ".text") {
+        for insn in text.data.chunks_exact(8) {
+            if insn[0] == 0x85 && insn[1] >> 4 == 0 {
+                let imm = u32_at(insn, 4).unwrap_or(0);
+                if let Some((_, name)) = hashes.iter().find(|(h, _)| *h == imm) {
+                    found.push(name.to_string());
+                }
+            }
+        }
+    }
+    found.sort();
+    found.dedup();
+    found
+}
+
+/// Printable ASCII runs of at least `min` bytes
+fn strings(data: &[u8], min: usize) -> Vec<&str> {
+    data.split(|b| !(0x20..0x7f).contains(b))
+        .filter(|s| s.len() >= min)
+        .filter_map(|s| std::str::from_utf8(s).ok())
+        .collect()
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+fn is_base58(c: char) -> bool {
+    c.is_ascii_alphanumeric() && !matches!(c, '0' | 'O' | 'I' | 'l')
+}
+
+/// Relative `.rs` paths, i.e. the program's own sources rather than crates from the registry
+fn source_files(text: &str, files: &mut Vec<String>) {
+    for (end, _) in text.match_indices(".rs") {
+        let mut start = text[..end].rfind(|c: char| !(c.is_ascii_alphanumeric() || "_-./".contains(c))).map_or(0, |i| i + 1);
+        // Literals are stored back to back, so the path may be glued to the previous string
+        if let Some(prev) = text[start..end].rfind(".rs") {
+            start += prev + 3;
+        }
+        let path = &text[start..end + 3];
+        if path.contains(".cargo") || path.contains("/rustc/") || path.contains("library/") {
+            continue;
+        }
+        let path = match path.find("programs/") {
+            Some(i) => &path[i..],
+            None => path.find("src/").map_or(path, |i| &path[i..]),
+        };
+        if path.len() > 3 && !path.starts_with('/') && !files.iter().any(|f| f == path) {
+            files.push(path.to_string());
+        }
+    }
+}
+
+/// Parse a deployed sBPF ELF. `program_id` is looked for among its constants.
+pub fn analyze(elf: &[u8], program_id: &str) -> Result<ElfReport, Box<dyn std::error::Error>> {
+    let sections = sections(elf)?;
+    let rodata: Vec<u8> = sections.iter()
+        .filter(|s| s.name.starts_with(".rodata") || s.name.starts_with(".data.rel.ro"))
+        .flat_map(|s| s.data.iter().copied())
+        .collect();
+    let text = strings(&rodata, 4).join("\n");
+
+    let mut known_programs = Vec::new();
+    let named = WELL_KNOWN.iter().copied()
+        .chain(crate::scanner::PROGRAMS.iter().map(|(id, name)| (*id, *name)))
+        .chain([(SQUADS_V3, "Squads v3"), (SQUADS_V4, "Squads v4")])
+        .chain(GOVERNANCE_PROGRAMS.iter().map(|id| (*id, "SPL Governance")));
+    for (address, name) in named {
+        if address != program_id && pubkey_bytes(address).is_ok_and(|key| contains(&rodata, &key)) {
+            known_programs.push((address.to_string(), name.to_string()));
+        }
+    }
+
+    let mut address_strings: Vec<String> = Vec::new();
+    for word in text.split(|c: char| !is_base58(c)) {
+        if (32..=44).contains(&word.len())
+            && bs58_decode(word).is_ok_and(|b| b.len() == 32)
+            && !address_strings.iter().any(|a| a == word)
+        {
+            address_strings.push(word.to_string());
+        }
+    }
+
+    let mut panics = Vec::new();
+    for (needle, kind) in PANICS {
+        let count = text.matches(needle).count();
+        if count > 0 {
+            panics.push((kind.to_string(), count));
+        }
+    }
+    let mut files = Vec::new();
+    source_files(&text, &mut files);
+
+    let anchor_version = text.find("anchor-lang-").map(|at| {
+        text[at + 12..].chars().take_while(|c| c.is_ascii_digit() || *c == '.').collect::<String>().trim_end_matches('.').to_string()
+    }).filter(|v| !v.is_empty());
+
+    Ok(ElfReport {
+        size: without_padding(elf).len(),
+        sbpf_version: u32_at(elf, 48).unwrap_or(0),
+        sections: sections.iter().filter(|s| !s.name.is_empty()).map(|s| (s.name.clone(), s.data.len() as u64)).collect(),
+        syscalls: syscalls(&sections),
+        anchor: anchor_version.is_some() || text.contains("AnchorError") || text.contains("anchor_lang"),
+        anchor_version,
+        known_programs,
+        address_strings,
+        declares_id: pubkey_bytes(program_id).is_ok_and(|key| contains(&rodata, &key)),
+        panics,
+        source_files: files,
+        security_txt: SecurityTxt::find(elf),
+    })
+}
+
+impl ElfReport {
+    fn imports(&self, name: &str) -> bool {
+        self.syscalls.iter().any(|s| s == name)
+    }
+
+    /// Rule hits for the audit
+    pub fn hits(&self) -> Vec<Hit> {
+        let mut hits = Vec::new();
+
+        if self.imports("sol_invoke_signed_rust") || self.imports("sol_invoke_signed_c") {
+            hits.push(Hit::new("cpi", "🔁 Makes cross-program invocations (sol_invoke_signed) — can move assets its PDAs hold".to_string()));
+        }
+        if self.imports("sol_set_return_data") {
+            hits.push(Hit::new("return_data", "↩️  Returns data to calling programs (sol_set_return_data)".to_string()));
+        }
+        let crypto: Vec<&str> = self.syscalls.iter().map(|s| s.as_str()).filter(|s| CRYPTO_SYSCALLS.contains(s)).collect();
+        if !crypto.is_empty() {
+            hits.push(Hit::new("custom_crypto", format!("🧮 Does its own cryptography ({}) — review signature/proof checks", crypto.join(", "))));
+        }
+        if self.imports("sol_alloc_free_") {
+            hits.push(Hit::new("deprecated_syscall", "🧓 Imports the deprecated sol_alloc_free_ syscall — built with a very old toolchain".to_string()));
+        }
+
+        match (&self.anchor, &self.anchor_version) {
+            (true, Some(version)) => hits.push(Hit::new("anchor", format!("⚓ Built with Anchor {}", version))),
+            (true, None) => hits.push(Hit::new("anchor", "⚓ Built with Anchor".to_string())),
+            (false, _) => hits.push(Hit::new("no_anchor", "🧰 Not built with Anchor — account validation is hand-written".to_string())),
+        }
+        // rustc only emits these messages when overflow checks are compiled in
+        if !self.source_files.is_empty() && !self.panics.iter().any(|(kind, _)| kind == "arithmetic overflow") {
+            hits.push(Hit::new("no_overflow_checks", "➕ No overflow-check panics in the binary — arithmetic may wrap silently".to_string()));
+        }
+        if !self.panics.is_empty() {
+            let total: usize = self.panics.iter().map(|(_, n)| n).sum();
+            let kinds: Vec<&str> = self.panics.iter().map(|(kind, _)| kind.as_str()).collect();
+            hits.push(Hit::new("panics", format!("💥 {} panic messages ({})", total, kinds.join(", "))));
+        }
+        if !self.known_programs.is_empty() {
+            let names: Vec<&str> = self.known_programs.iter().map(|(_, name)| name.as_str()).collect();
+            hits.push(Hit::new("hardcoded_programs", format!("🔗 Hard-codes {}", names.join(", "))));
+        }
+        if !self.address_strings.is_empty() {
+            hits.push(Hit::new("address_strings", format!(
+                "🔑 {} address string(s) compiled in, e.g. {} — check for admin keys",
+                self.address_strings.len(), self.address_strings[0]
+            )));
+        }
+        match &self.security_txt {
+            Some(txt) => hits.push(Hit::new("security_txt", format!("📇 security.txt: {}", txt.summary()))),
+            None => hits.push(Hit::new("no_security_txt", "ℹ️  No security.txt — no published way to report vulnerabilities".to_string())),
+        }
+        if !self.declares_id {
+            hits.push(Hit::new("no_declared_id", "🪪 Its own program id is not compiled in — it cannot check it was invoked as itself".to_string()));
+        }
+        hits
+    }
+
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({
+            "size": self.size,
+            "sbpf_version": self.sbpf_version,
+            "sections": self.sections.iter().map(|(name, size)| serde_json::json!({ "name": name, "size": size })).collect::<Vec<_>>(),
+            "syscalls": self.syscalls,
+            "anchor": self.anchor,
+            "anchor_version": self.anchor_version,
+            "known_programs": self.known_programs.iter().map(|(address, name)| serde_json::json!({ "address": address, "name": name })).collect::<Vec<_>>(),
+            "address_strings": self.address_strings,
+            "declares_id": self.declares_id,
+            "panics": self.panics.iter().map(|(kind, count)| serde_json::json!({ "kind": kind, "count": count })).collect::<Vec<_>>(),
+            "source_files": self.source_files,
+            "security_txt": self.security_txt.as_ref().map(|t| t.to_json()),
+        })
+    }
+}
+
+pub fn print_report(report: &ElfReport) {
+    println!("\n  Bytecode ({} bytes, sBPF v{}):", report.size, report.sbpf_version);
+    let sections: Vec<String> = report.sections.iter()
+        .filter(|(name, size)| *size > 0 && (name.starts_with(".text") || name.starts_with(".rodata") || name.starts_with(".data") || name.starts_with(".bss")))
+        .map(|(name, size)| format!("{} {}", name, size))
+        .collect();
+    println!("    Sections: {}", sections.join(", "));
+    println!("    Syscalls: {}", if report.syscalls.is_empty() { "none".to_string() } else { report.syscalls.join(", ") });
+    if !report.source_files.is_empty() {
+        let shown: Vec<&str> = report.source_files.iter().take(5).map(|f| f.as_str()).collect();
+        println!("    Sources: {}{}", shown.join(", "), if report.source_files.len() > 5 { ", ..." } else { "" });
+    }
+    match &report.security_txt {
+        Some(txt) => {
+            println!("\n  security.txt:");
+            for (key, value) in &txt.fields {
+                println!("    {:20} {}", key, value);
+            }
+        }
+        None => println!("\n  security.txt: none"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn syscall_hashes() {
+        assert_eq!(murmur3_32(b"sol_log_"), 0x207559bd);
+        assert_eq!(murmur3_32(b"abort"), 0xb6fc1a11);
+        assert_eq!(murmur3_32(b"sol_invoke_signed_rust"), 0xd7449092);
+    }
+
+    #[test]
+    fn section_headers_out_of_range() {
+        let mut elf = vec![0u8; 64];
+        elf[..6].copy_from_slice(b"\x7fELF\x02\x01");
+        elf[18..20].copy_from_slice(&247u16.to_le_bytes());
+        elf[58..60].copy_from_slice(&64u16.to_le_bytes());
+        elf[60..62].copy_from_slice(&2u16.to_le_bytes());
+        for shoff in [u64::MAX - 10, 64] {
+            elf[40..48].copy_from_slice(&shoff.to_le_bytes());
+            assert_eq!(sections(&elf).err().unwrap().to_string(), "section headers past end of file");
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::audit::{ContractAudit, AuditResult};
-use std::collections::HashMap;
+use crate::rules::RuleSet;
+use crate::watchlist::WatchedProgram;
 
 /// Known Solana DeFi programs to audit
 pub const PROGRAMS: &[(&str, &str)] = &[
@@ -20,11 +21,16 @@ pub const PROGRAMS: &[(&str, &str)] = &[
     ("MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD", "Marinade Finance"),
 ];
 
-pub async fn scan_all(rpc_url: &str) -> Vec<(String, AuditResult)> {
-    let auditor = ContractAudit::new(rpc_url.to_string());
+/// Name of a program from the known-protocol table
+pub fn program_name(program_id: &str) -> Option<&'static str> {
+    PROGRAMS.iter().find(|(id, _)| *id == program_id).map(|(_, name)| *name)
+}
+
+pub async fn scan_all(rpc_url: &str, programs: &[WatchedProgram], rules: RuleSet) -> Vec<(String, AuditResult)> {
+    let auditor = ContractAudit::new(rpc_url.to_string(), rules);
     let mut results = Vec::new();
     
-    for (program_id, name) in PROGRAMS {
+    for WatchedProgram { id: program_id, name, .. } in programs {
         eprint!("  Auditing {}... ", name);
         match auditor.audit(program_id).await {
             Ok(result) => {
@@ -59,6 +65,20 @@ pub fn print_report(results: &[(String, AuditResult)]) {
             r.risk_score);
     }
     
+    println!("\n  📇 Security contacts (security.txt; every field with --json):");
+    for (name, r) in results {
+        match r.elf.as_ref().map(|e| &e.security_txt) {
+            Some(Some(txt)) => {
+                println!("    {}", name);
+                for key in ["name", "contacts", "policy", "source_code", "auditors"] {
+                    println!("      {:12} {}", key, txt.get(key).unwrap_or("-"));
+                }
+            }
+            Some(None) => println!("    {:28} none embedded", name),
+            None => println!("    {:28} code not readable", name),
+        }
+    }
+    
     let avg_risk: f64 = results.iter().map(|(_, r)| r.risk_score as f64).sum::<f64>() / results.len().max(1) as f64;
     let upgradeable_count = results.iter().filter(|(_, r)| r.is_upgradeable).count();
     
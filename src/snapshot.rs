@@ -0,0 +1,404 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Liquid staking tokens reported as DeFi positions
+pub const LIQUID_STAKING: &[(&str, &str)] = &[
+    ("mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So", "Marinade mSOL"),
+    ("J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn", "Jito jitoSOL"),
+    ("bSo13r4TkiE4KumL71LsHTPpL2euBYLFx6h9HP3piy1", "BlazeStake bSOL"),
+    ("jupSoLaHXQiZZTSfEWMTRRgpnyFm8f6sZdosWBjx93v", "Jupiter JupSOL"),
+];
+
+/// Point-in-time record of everything a wallet holds
+pub struct Snapshot {
+    pub wallet: String,
+    pub slot: u64,
+    /// Unix seconds
+    pub timestamp: i64,
+    pub sol_balance: f64,
+    /// mint -> holding, summed across token accounts
+    pub tokens: BTreeMap<String, TokenHolding>,
+    /// Err when the stake accounts couldn't be listed; many public RPCs refuse that query
+    pub stake: Result<Vec<StakeHolding>, String>,
+    pub defi: Vec<DefiPosition>,
+}
+
+pub struct TokenHolding {
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+impl TokenHolding {
+    pub fn ui_amount(&self) -> f64 {
+        self.amount as f64 / 10f64.powi(self.decimals as i32)
+    }
+}
+
+#[derive(Clone)]
+pub struct StakeHolding {
+    pub address: String,
+    pub lamports: u64,
+    pub state: String,
+    pub voter: Option<String>,
+}
+
+pub struct DefiPosition {
+    pub protocol: String,
+    pub mint: String,
+    pub amount: f64,
+}
+
+impl Snapshot {
+    pub fn time_str(&self) -> String {
+        chrono::DateTime::from_timestamp(self.timestamp, 0)
+            .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| format!("ts:{}", self.timestamp))
+    }
+
+    /// None when the stake accounts are unknown
+    pub fn staked_sol(&self) -> Option<f64> {
+        let stake = self.stake.as_ref().ok()?;
+        Some(stake.iter().map(|s| s.lamports).sum::<u64>() as f64 / crate::LAMPORTS_PER_SOL)
+    }
+
+    /// Number of stake accounts, or "?" when unknown
+    pub fn stake_count(&self) -> String {
+        self.stake.as_ref().map_or_else(|_| "?".to_string(), |s| s.len().to_string())
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "wallet": self.wallet,
+            "slot": self.slot,
+            "timestamp": self.timestamp,
+            "time": self.time_str(),
+            "sol_balance": self.sol_balance,
+            "tokens": self.tokens.iter().map(|(mint, t)| serde_json::json!({
+                "mint": mint,
+                "amount": t.amount.to_string(),
+                "decimals": t.decimals,
+                "balance": t.ui_amount(),
+            })).collect::<Vec<_>>(),
+            "stake": self.stake.as_ref().ok().map(|stake| stake.iter().map(|s| serde_json::json!({
+                "address": s.address,
+                "lamports": s.lamports,
+                "state": s.state,
+                "voter": s.voter,
+            })).collect::<Vec<_>>()),
+            "stake_error": self.stake.as_ref().err(),
+            "defi": self.defi.iter().map(|d| serde_json::json!({
+                "protocol": d.protocol,
+                "mint": d.mint,
+                "amount": d.amount,
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    pub fn from_json(v: &serde_json::Value) -> Option<Snapshot> {
+        let tokens = v["tokens"].as_array()?.iter()
+            .filter_map(|t| Some((
+                t["mint"].as_str()?.to_string(),
+                TokenHolding {
+                    amount: t["amount"].as_str()?.parse().ok()?,
+                    decimals: t["decimals"].as_u64()? as u8,
+                },
+            )))
+            .collect();
+        let stake = match v["stake"].as_array() {
+            Some(stake) => Ok(stake.iter()
+                .filter_map(|s| Some(StakeHolding {
+                    address: s["address"].as_str()?.to_string(),
+                    lamports: s["lamports"].as_u64()?,
+                    state: s["state"].as_str().unwrap_or("unknown").to_string(),
+                    voter: s["voter"].as_str().map(|v| v.to_string()),
+                }))
+                .collect()),
+            None => Err(v["stake_error"].as_str().unwrap_or("not recorded").to_string()),
+        };
+        let defi = v["defi"].as_array()?.iter()
+            .filter_map(|d| Some(DefiPosition {
+                protocol: d["protocol"].as_str()?.to_string(),
+                mint: d["mint"].as_str()?.to_string(),
+                amount: d["amount"].as_f64()?,
+            }))
+            .collect();
+        Some(Snapshot {
+            wallet: v["wallet"].as_str()?.to_string(),
+            slot: v["slot"].as_u64()?,
+            timestamp: v["timestamp"].as_i64()?,
+            sol_balance: v["sol_balance"].as_f64()?,
+            tokens,
+            stake,
+            defi,
+        })
+    }
+}
+
+/// Scan the wallet's current SOL, tokens, stake and DeFi positions
+pub async fn capture(client: &reqwest::Client, wallet: &str) -> Result<Snapshot, Box<dyn std::error::Error>> {
+    let slot = crate::get_slot(client).await?;
+    let sol_balance = crate::get_sol_balance(client, wallet).await?;
+
+    let mut tokens: BTreeMap<String, TokenHolding> = BTreeMap::new();
+    for account in crate::get_parsed_token_accounts(client, wallet).await? {
+        if account.raw_amount() == 0 { continue; }
+        let holding = tokens.entry(account.mint().to_string())
+            .or_insert(TokenHolding { amount: 0, decimals: account.decimals() });
+        holding.amount = holding.amount.saturating_add(account.raw_amount());
+    }
+
+    // Recorded as unknown rather than empty, so a refused query doesn't read as unstaking
+    let stake = crate::get_stake_accounts(client, wallet).await
+        .map(|accounts| accounts.into_iter()
+            .map(|s| StakeHolding {
+                address: s.address,
+                lamports: s.lamports,
+                state: if s.deactivating { "deactivating".to_string() } else { s.state },
+                voter: s.voter,
+            })
+            .collect())
+        .map_err(|e| e.to_string());
+
+    let defi = LIQUID_STAKING.iter()
+        .filter_map(|(mint, protocol)| tokens.get(*mint).map(|t| DefiPosition {
+            protocol: protocol.to_string(),
+            mint: mint.to_string(),
+            amount: t.ui_amount(),
+        }))
+        .collect();
+
+    Ok(Snapshot {
+        wallet: wallet.to_string(),
+        slot,
+        timestamp: chrono::Utc::now().timestamp(),
+        sol_balance,
+        tokens,
+        stake,
+        defi,
+    })
+}
+
+// === Local store: <data_dir>/snapshots/<wallet>/<slot>.json ===
+
+fn wallet_dir(wallet: &str) -> PathBuf {
+    crate::data_dir().join("snapshots").join(wallet)
+}
+
+pub fn save(snapshot: &Snapshot) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let dir = wallet_dir(&snapshot.wallet);
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}.json", snapshot.slot));
+    std::fs::write(&path, serde_json::to_string_pretty(&snapshot.to_json())?)?;
+    Ok(path)
+}
+
+/// All saved snapshots for a wallet, oldest first
+pub fn list(wallet: &str) -> Result<Vec<Snapshot>, Box<dyn std::error::Error>> {
+    let dir = wallet_dir(wallet);
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+    let mut snapshots = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") { continue; }
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                eprintln!("  ⚠️  Skipping unreadable snapshot {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let value: serde_json::Value = match serde_json::from_str(&text) {
+            Ok(value) => value,
+            Err(e) => {
+                eprintln!("  ⚠️  Skipping unreadable snapshot {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        match Snapshot::from_json(&value) {
+            Some(s) => snapshots.push(s),
+            None => eprintln!("  ⚠️  Skipping unreadable snapshot {}", path.display()),
+        }
+    }
+    snapshots.sort_by_key(|s| s.slot);
+    Ok(snapshots)
+}
+
+/// Pick a saved snapshot: "latest", a slot number, or a date (YYYY-MM-DD → last snapshot that day or before)
+pub fn select(wallet: &str, selector: &str) -> Result<Snapshot, Box<dyn std::error::Error>> {
+    let mut snapshots = list(wallet)?;
+    let index = if selector == "latest" {
+        snapshots.len().checked_sub(1)
+    } else if let Ok(slot) = selector.parse::<u64>() {
+        snapshots.iter().position(|s| s.slot == slot)
+    } else if let Ok(date) = chrono::NaiveDate::parse_from_str(selector, "%Y-%m-%d") {
+        let end_of_day = date.and_hms_opt(23, 59, 59)
+            .and_then(|t| t.and_local_timezone(chrono::Local).latest())
+            .map(|t| t.timestamp())
+            .unwrap_or(i64::MAX);
+        snapshots.iter().rposition(|s| s.timestamp <= end_of_day)
+    } else {
+        return Err(format!("invalid snapshot selector '{}' (use latest, a slot, or YYYY-MM-DD)", selector).into());
+    };
+    match index {
+        Some(i) => Ok(snapshots.swap_remove(i)),
+        None => Err(format!("no snapshot matching '{}' for {}", selector, wallet).into()),
+    }
+}
+
+// === Diffing ===
+
+pub struct SnapshotDiff<'a> {
+    pub from: &'a Snapshot,
+    pub to: &'a Snapshot,
+    /// (mint, before, after) — before 0 means new, after 0 means fully sent
+    pub tokens: Vec<(String, f64, f64)>,
+    /// None when either snapshot doesn't know its stake accounts
+    pub stake: Option<Vec<(Option<StakeHolding>, Option<StakeHolding>)>>,
+    /// (protocol, before, after)
+    pub defi: Vec<(String, f64, f64)>,
+}
+
+impl SnapshotDiff<'_> {
+    pub fn sol_change(&self) -> f64 {
+        self.to.sol_balance - self.from.sol_balance
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sol_change().abs() < 1e-9 && self.tokens.is_empty()
+            && self.stake.as_ref().is_none_or(|s| s.is_empty()) && self.defi.is_empty()
+    }
+}
+
+pub fn diff<'a>(from: &'a Snapshot, to: &'a Snapshot) -> SnapshotDiff<'a> {
+    let mut tokens = Vec::new();
+    for (mint, before) in &from.tokens {
+        let after = to.tokens.get(mint).map_or(0, |t| t.amount);
+        if after != before.amount {
+            tokens.push((mint.clone(), before.ui_amount(), to.tokens.get(mint).map_or(0.0, |t| t.ui_amount())));
+        }
+    }
+    for (mint, after) in &to.tokens {
+        if !from.tokens.contains_key(mint) {
+            tokens.push((mint.clone(), 0.0, after.ui_amount()));
+        }
+    }
+
+    let stake = match (&from.stake, &to.stake) {
+        (Ok(from_stake), Ok(to_stake)) => {
+            let mut stake = Vec::new();
+            for before in from_stake {
+                let after = to_stake.iter().find(|s| s.address == before.address);
+                match after {
+                    Some(a) if a.lamports == before.lamports && a.state == before.state && a.voter == before.voter => {}
+                    _ => stake.push((Some(before.clone()), after.cloned())),
+                }
+            }
+            for after in to_stake {
+                if !from_stake.iter().any(|s| s.address == after.address) {
+                    stake.push((None, Some(after.clone())));
+                }
+            }
+            Some(stake)
+        }
+        _ => None,
+    };
+
+    let defi = LIQUID_STAKING.iter()
+        .map(|(mint, protocol)| {
+            let amount = |s: &Snapshot| s.defi.iter().find(|d| d.mint == *mint).map_or(0.0, |d| d.amount);
+            (protocol.to_string(), amount(from), amount(to))
+        })
+        .filter(|(_, before, after)| (before - after).abs() > 0.0)
+        .collect();
+
+    SnapshotDiff { from, to, tokens, stake, defi }
+}
+
+pub fn diff_to_json(d: &SnapshotDiff) -> serde_json::Value {
+    let stake_json = |s: &Option<StakeHolding>| s.as_ref().map(|s| serde_json::json!({
+        "lamports": s.lamports, "state": s.state, "voter": s.voter,
+    }));
+    serde_json::json!({
+        "wallet": d.from.wallet,
+        "from": { "slot": d.from.slot, "time": d.from.time_str() },
+        "to": { "slot": d.to.slot, "time": d.to.time_str() },
+        "sol_change": d.sol_change(),
+        "tokens": d.tokens.iter().map(|(mint, before, after)| serde_json::json!({
+            "mint": mint, "before": before, "after": after, "change": after - before,
+        })).collect::<Vec<_>>(),
+        "stake": d.stake.as_ref().map(|stake| stake.iter().map(|(before, after)| serde_json::json!({
+            "address": before.as_ref().or(after.as_ref()).map(|s| s.address.clone()),
+            "before": stake_json(before),
+            "after": stake_json(after),
+        })).collect::<Vec<_>>()),
+        "defi": d.defi.iter().map(|(protocol, before, after)| serde_json::json!({
+            "protocol": protocol, "before": before, "after": after, "change": after - before,
+        })).collect::<Vec<_>>(),
+    })
+}
+
+pub fn print_list(wallet: &str, snapshots: &[Snapshot]) {
+    println!("\n📸 Snapshots for {}...{}", &wallet[..8], &wallet[wallet.len()-4..]);
+    println!("═══════════════════════════════════════════");
+    if snapshots.is_empty() {
+        println!("  None yet — solscan snapshot save {}", wallet);
+        return;
+    }
+    println!("  {:>12}  {:19}  {:>14}  {:>6}  {:>5}", "Slot", "Time", "SOL", "Tokens", "Stake");
+    for s in snapshots {
+        println!("  {:>12}  {:19}  {:>14.6}  {:>6}  {:>5}",
+            s.slot, s.time_str(), s.sol_balance, s.tokens.len(), s.stake_count());
+    }
+}
+
+pub fn print_diff(d: &SnapshotDiff) {
+    let wallet = &d.from.wallet;
+    println!("\n📸 Snapshot Diff: {}...{}", &wallet[..8], &wallet[wallet.len()-4..]);
+    println!("═══════════════════════════════════════════");
+    println!("  From: slot {} ({})", d.from.slot, d.from.time_str());
+    println!("  To:   slot {} ({})", d.to.slot, d.to.time_str());
+    if d.stake.is_none() {
+        println!("  ⚠️  Stake accounts not compared: one of the snapshots couldn't list them");
+    }
+
+    if d.is_empty() {
+        println!("\n  No {}changes ✅", if d.stake.is_none() { "other " } else { "" });
+        return;
+    }
+
+    let sign = |x: f64| if x > 0.0 { "+" } else { "" };
+    if d.sol_change().abs() >= 1e-9 {
+        println!("\n  💰 SOL: {:.6} → {:.6} ({}{:.6})",
+            d.from.sol_balance, d.to.sol_balance, sign(d.sol_change()), d.sol_change());
+    }
+
+    if !d.tokens.is_empty() {
+        println!("\n  📦 Tokens:");
+        for (mint, before, after) in &d.tokens {
+            let tag = if *before == 0.0 { "🆕" } else if *after == 0.0 { "📤" } else { "  " };
+            println!("    {} {}...{}: {} → {} ({}{})",
+                tag, &mint[..8], &mint[mint.len()-4..], before, after, sign(after - before), after - before);
+        }
+    }
+
+    if let Some(stake) = d.stake.as_ref().filter(|s| !s.is_empty()) {
+        println!("\n  🥩 Stake Accounts:");
+        for (before, after) in stake {
+            let describe = |s: &Option<StakeHolding>| match s {
+                Some(s) => format!("{:.4} SOL {}", s.lamports as f64 / crate::LAMPORTS_PER_SOL, s.state),
+                None => "—".to_string(),
+            };
+            let addr = before.as_ref().or(after.as_ref()).map(|s| s.address.as_str()).unwrap_or("");
+            println!("    {}...{}: {} → {}", &addr[..8], &addr[addr.len()-4..], describe(before), describe(after));
+        }
+    }
+
+    if !d.defi.is_empty() {
+        println!("\n  🏦 DeFi Positions:");
+        for (protocol, before, after) in &d.defi {
+            println!("    {}: {} → {}", protocol, before, after);
+        }
+    }
+}
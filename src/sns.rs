@@ -0,0 +1,141 @@
+use crate::crypto::{find_program_address, sha256};
+use crate::encoding::{base64_decode, bs58_encode, pubkey_bytes};
+use std::collections::HashMap;
+
+/// Solana Name Service (.sol domains)
+const NAME_PROGRAM_ID: &str = "namesLPneVptA9Z5rqUDD9tMTWEJwofgaYwp8cawRkX";
+/// Parent name account of every .sol domain
+const SOL_TLD: &str = "58PwtjSDuFHuUkYjH9BYnnQKHfwo9reZhC2zMJv9JPkx";
+/// Class of reverse-lookup records (name account → domain string)
+const REVERSE_LOOKUP_CLASS: &str = "33m47vH6Eav6jr5Ry86XjhRft2jRBLDnDgPSHoquXi2Z";
+/// Program holding each wallet's chosen primary ("favourite") domain
+const NAME_OFFERS_PROGRAM_ID: &str = "85iDfUvr3HJyLM2zcq5BXSiDvUWfw6cSE1FfNBo8Ap29";
+const HASH_PREFIX: &str = "SPL Name Service";
+/// Name registry header: [32 parent][32 owner][32 class]
+const HEADER_LEN: usize = 96;
+
+pub fn is_domain(input: &str) -> bool {
+    input.ends_with(".sol")
+}
+
+fn hashed_name(name: &str) -> [u8; 32] {
+    sha256(format!("{}{}", HASH_PREFIX, name).as_bytes())
+}
+
+fn name_account_key(hashed: &[u8; 32], class: Option<&[u8; 32]>, parent: Option<&[u8; 32]>) -> [u8; 32] {
+    let program = pubkey_bytes(NAME_PROGRAM_ID).expect("valid program id");
+    let zero = [0u8; 32];
+    let (key, _) = find_program_address(
+        &[hashed, class.unwrap_or(&zero), parent.unwrap_or(&zero)],
+        &program,
+    );
+    key
+}
+
+/// Name account for `name.sol` or `sub.name.sol`
+pub fn domain_key(domain: &str) -> Result<[u8; 32], Box<dyn std::error::Error>> {
+    let name = domain.strip_suffix(".sol").unwrap_or(domain);
+    let tld = pubkey_bytes(SOL_TLD)?;
+    match name.split('.').collect::<Vec<_>>().as_slice() {
+        [name] if !name.is_empty() => Ok(name_account_key(&hashed_name(name), None, Some(&tld))),
+        [sub, name] if !sub.is_empty() && !name.is_empty() => {
+            let parent = name_account_key(&hashed_name(name), None, Some(&tld));
+            // Subdomains are hashed with a leading NUL
+            Ok(name_account_key(&hashed_name(&format!("\0{}", sub)), None, Some(&parent)))
+        }
+        _ => Err(format!("invalid .sol domain '{}'", domain).into()),
+    }
+}
+
+async fn account_data(client: &reqwest::Client, address: &str) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+    let result = crate::rpc_call(client, "getAccountInfo", serde_json::json!([address, { "encoding": "base64" }])).await?;
+    match result["value"]["data"].as_array().and_then(|a| a.first()).and_then(|v| v.as_str()) {
+        Some(b64) => Ok(Some(base64_decode(b64)?)),
+        None => Ok(None),
+    }
+}
+
+/// Owner of a .sol domain's name registry account
+pub async fn resolve(client: &reqwest::Client, domain: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let key = bs58_encode(&domain_key(domain)?);
+    let data = account_data(client, &key).await?
+        .ok_or_else(|| format!("{} is not registered", domain))?;
+    if data.len() < HEADER_LEN {
+        return Err(format!("{}: malformed name registry", domain).into());
+    }
+    Ok(bs58_encode(&data[32..64]))
+}
+
+/// Accept either a base58 address or a .sol domain
+pub async fn resolve_address(client: &reqwest::Client, input: &str) -> Result<String, Box<dyn std::error::Error>> {
+    if !is_domain(input) {
+        return Ok(input.to_string());
+    }
+    let owner = resolve(client, input).await?;
+    eprintln!("  🌐 {} → {}", input, owner);
+    Ok(owner)
+}
+
+/// The wallet's primary domain, if it set one and still owns it
+pub async fn primary_domain(client: &reqwest::Client, wallet: &str) -> Option<String> {
+    let offers = pubkey_bytes(NAME_OFFERS_PROGRAM_ID).ok()?;
+    let wallet_bytes = pubkey_bytes(wallet).ok()?;
+    let (favourite, _) = find_program_address(&[b"favourite_domain", &wallet_bytes], &offers);
+
+    // FavouriteDomain: [1 tag][32 name account]
+    let data = account_data(client, &bs58_encode(&favourite)).await.ok()??;
+    let name_account: [u8; 32] = data.get(1..33)?.try_into().ok()?;
+
+    // A stale favourite (domain since transferred) doesn't count
+    let registry = account_data(client, &bs58_encode(&name_account)).await.ok()??;
+    if registry.get(32..64)? != wallet_bytes.as_slice() {
+        return None;
+    }
+
+    // A subdomain's reverse record only holds its own label; the parent's holds the rest
+    let parent: [u8; 32] = registry.get(..32)?.try_into().ok()?;
+    let tld = pubkey_bytes(SOL_TLD).ok()?;
+    if parent == tld {
+        let name = reverse_name(client, &name_account, None).await?;
+        return Some(format!("{}.sol", name));
+    }
+    let sub = reverse_name(client, &name_account, Some(&parent)).await?;
+    let name = reverse_name(client, &parent, None).await?;
+    Some(format!("{}.{}.sol", sub.trim_start_matches('\0'), name))
+}
+
+/// Name stored in a name account's reverse record
+async fn reverse_name(client: &reqwest::Client, name_account: &[u8; 32], parent: Option<&[u8; 32]>) -> Option<String> {
+    let class = pubkey_bytes(REVERSE_LOOKUP_CLASS).ok()?;
+    let reverse_key = name_account_key(&hashed_name(&bs58_encode(name_account)), Some(&class), parent);
+    let reverse = account_data(client, &bs58_encode(&reverse_key)).await.ok()??;
+    // Reverse record data: borsh string (u32 length + bytes)
+    let body = reverse.get(HEADER_LEN..)?;
+    let len = u32::from_le_bytes(body.get(..4)?.try_into().ok()?) as usize;
+    String::from_utf8(body.get(4..4 + len)?.to_vec()).ok()
+}
+
+/// Primary domains for many wallets (wallets without one are omitted)
+pub async fn primary_domains(client: &reqwest::Client, wallets: &[String]) -> HashMap<String, String> {
+    let mut domains = HashMap::new();
+    for wallet in wallets {
+        if let Some(domain) = primary_domain(client, wallet).await {
+            domains.insert(wallet.clone(), domain);
+        }
+    }
+    domains
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn domain_keys() {
+        assert_eq!(bs58_encode(&domain_key("bonfida.sol").unwrap()), "Crf8hzfthWGbGbLTVCiqRqV5MVnbpHB1L9KQMd6gsinb");
+        assert_eq!(domain_key("bonfida").unwrap(), domain_key("bonfida.sol").unwrap());
+        assert_eq!(bs58_encode(&domain_key("dex.bonfida.sol").unwrap()), "HoFfFXqFHAC8RP3duuQNzag1ieUwJRBv1HtRNiWFq4Qu");
+        assert!(domain_key(".sol").is_err());
+        assert!(domain_key("a.b.c.sol").is_err());
+    }
+}
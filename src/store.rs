@@ -0,0 +1,150 @@
+use crate::daemon::{Alert, ProgramState};
+use crate::incidents::Incident;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Guardian state on disk, under `$SOLSCAN_HOME/guardian`:
+/// `state.json` holds the last known `ProgramState` per program and the open incidents,
+/// `alerts.jsonl` is an append-only log of every alert raised,
+/// `acks.jsonl` records `guardian ack` commands for the running daemon to pick up.
+pub struct GuardianStore {
+    dir: PathBuf,
+}
+
+/// Program states as of the last completed cycle
+pub struct SavedState {
+    pub saved_at: i64,
+    pub programs: HashMap<String, ProgramState>,
+    pub incidents: Vec<Incident>,
+}
+
+/// An alert from the history log
+pub struct StoredAlert {
+    pub recorded_at: i64,
+    pub alert: Alert,
+}
+
+impl StoredAlert {
+    pub fn time_str(&self) -> String {
+        chrono::DateTime::from_timestamp(self.recorded_at, 0)
+            .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| self.recorded_at.to_string())
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut json = self.alert.to_json();
+        json["recorded_at"] = serde_json::json!(self.recorded_at);
+        json
+    }
+}
+
+impl GuardianStore {
+    pub fn open() -> Result<Self, Box<dyn std::error::Error>> {
+        let dir = crate::data_dir().join("guardian");
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn states_path(&self) -> PathBuf {
+        self.dir.join("state.json")
+    }
+
+    fn alerts_path(&self) -> PathBuf {
+        self.dir.join("alerts.jsonl")
+    }
+
+    pub fn load_state(&self) -> Result<Option<SavedState>, Box<dyn std::error::Error>> {
+        let path = self.states_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path)?)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        let programs = json["programs"].as_object().into_iter().flatten()
+            .filter_map(|(id, state)| Some((id.clone(), ProgramState::from_json(state)?)))
+            .collect();
+        Ok(Some(SavedState {
+            saved_at: json["saved_at"].as_i64().unwrap_or(0),
+            programs,
+            incidents: json["incidents"].as_array().into_iter().flatten()
+                .filter_map(Incident::from_json)
+                .collect(),
+        }))
+    }
+
+    /// Written to a temp file and renamed, so a crash mid-write never leaves a torn state
+    pub fn save_state<'a>(
+        &self,
+        programs: &HashMap<String, ProgramState>,
+        incidents: impl Iterator<Item = &'a Incident>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let json = serde_json::json!({
+            "saved_at": chrono::Utc::now().timestamp(),
+            "programs": programs.iter()
+                .map(|(id, state)| (id.clone(), state.to_json()))
+                .collect::<serde_json::Map<_, _>>(),
+            "incidents": incidents.map(|i| i.to_json()).collect::<Vec<_>>(),
+        });
+        let tmp = self.dir.join("state.json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(&json)?)?;
+        std::fs::rename(tmp, self.states_path())?;
+        Ok(())
+    }
+
+    pub fn append_alerts(&self, alerts: &[Alert]) -> Result<(), Box<dyn std::error::Error>> {
+        if alerts.is_empty() {
+            return Ok(());
+        }
+        let recorded_at = chrono::Utc::now().timestamp();
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(self.alerts_path())?;
+        let mut lines = String::new();
+        for alert in alerts {
+            let stored = StoredAlert { recorded_at, alert: alert.clone() };
+            lines.push_str(&stored.to_json().to_string());
+            lines.push('\n');
+        }
+        file.write_all(lines.as_bytes())?;
+        Ok(())
+    }
+
+    /// Full alert history, oldest first. Unreadable lines are skipped.
+    pub fn alerts(&self) -> Result<Vec<StoredAlert>, Box<dyn std::error::Error>> {
+        let path = self.alerts_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(std::fs::read_to_string(path)?
+            .lines()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .filter_map(|json| Some(StoredAlert {
+                recorded_at: json["recorded_at"].as_i64()?,
+                alert: Alert::from_json(&json)?,
+            }))
+            .collect())
+    }
+
+    fn acks_path(&self) -> PathBuf {
+        self.dir.join("acks.jsonl")
+    }
+
+    pub fn append_ack(&self, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(self.acks_path())?;
+        let line = serde_json::json!({ "id": id, "acked_at": chrono::Utc::now().timestamp() });
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+
+    /// Every acknowledgement given, as `(alert id, unix time)`
+    pub fn acks(&self) -> Result<Vec<(String, i64)>, Box<dyn std::error::Error>> {
+        let path = self.acks_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(std::fs::read_to_string(path)?
+            .lines()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .filter_map(|json| Some((json["id"].as_str()?.to_string(), json["acked_at"].as_i64()?)))
+            .collect())
+    }
+}
@@ -0,0 +1,100 @@
+use crate::encoding::{base64_encode, pubkey_bytes};
+
+/// Minimal legacy transaction builder — just enough to hand unsigned
+/// transactions to an external wallet for signing
+pub struct Instruction {
+    pub program_id: String,
+    pub accounts: Vec<AccountMeta>,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone)]
+pub struct AccountMeta {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl AccountMeta {
+    pub fn writable(pubkey: &str, is_signer: bool) -> Self {
+        Self { pubkey: pubkey.to_string(), is_signer, is_writable: true }
+    }
+}
+
+/// Serialize a transaction with empty signature slots, base64-encoded
+pub fn unsigned_transaction(
+    fee_payer: &str,
+    instructions: &[Instruction],
+    recent_blockhash: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    // Collect every account with merged signer/writable flags, fee payer first
+    let mut metas: Vec<AccountMeta> = vec![AccountMeta::writable(fee_payer, true)];
+    let mut add = |meta: AccountMeta| {
+        match metas.iter_mut().find(|m| m.pubkey == meta.pubkey) {
+            Some(m) => {
+                m.is_signer |= meta.is_signer;
+                m.is_writable |= meta.is_writable;
+            }
+            None => metas.push(meta),
+        }
+    };
+    for ix in instructions {
+        for meta in &ix.accounts {
+            add(meta.clone());
+        }
+        add(AccountMeta { pubkey: ix.program_id.clone(), is_signer: false, is_writable: false });
+    }
+
+    // Message order: signed+writable, signed+readonly, writable, readonly
+    let rank = |m: &AccountMeta| match (m.is_signer, m.is_writable) {
+        (true, true) => 0,
+        (true, false) => 1,
+        (false, true) => 2,
+        (false, false) => 3,
+    };
+    let (payer, rest) = metas.split_at_mut(1);
+    rest.sort_by_key(|m| rank(m));
+    let keys: Vec<&AccountMeta> = payer.iter().chain(rest.iter()).collect();
+
+    let num_signers = keys.iter().filter(|m| m.is_signer).count();
+    let readonly_signed = keys.iter().filter(|m| m.is_signer && !m.is_writable).count();
+    let readonly_unsigned = keys.iter().filter(|m| !m.is_signer && !m.is_writable).count();
+    let index_of = |pubkey: &str| keys.iter().position(|m| m.pubkey == pubkey).unwrap_or(0) as u8;
+
+    let mut message = vec![num_signers as u8, readonly_signed as u8, readonly_unsigned as u8];
+    push_compact_u16(&mut message, keys.len());
+    for meta in &keys {
+        message.extend_from_slice(&pubkey_bytes(&meta.pubkey)?);
+    }
+    message.extend_from_slice(&pubkey_bytes(recent_blockhash)?);
+    push_compact_u16(&mut message, instructions.len());
+    for ix in instructions {
+        message.push(index_of(&ix.program_id));
+        push_compact_u16(&mut message, ix.accounts.len());
+        for meta in &ix.accounts {
+            message.push(index_of(&meta.pubkey));
+        }
+        push_compact_u16(&mut message, ix.data.len());
+        message.extend_from_slice(&ix.data);
+    }
+
+    let mut tx = Vec::with_capacity(1 + num_signers * 64 + message.len());
+    push_compact_u16(&mut tx, num_signers);
+    tx.extend(std::iter::repeat_n(0u8, num_signers * 64));
+    tx.extend_from_slice(&message);
+    Ok(base64_encode(&tx))
+}
+
+/// Solana's "shortvec" length prefix
+fn push_compact_u16(out: &mut Vec<u8>, mut value: usize) {
+    loop {
+        let mut byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        byte |= 0x80;
+        out.push(byte);
+    }
+}
@@ -0,0 +1,432 @@
+use crate::alerts::AlertRouter;
+use crate::daemon::{Alert, Severity};
+use crate::portfolio::{self, PortfolioEntry};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Total holding of one mint across all of the wallet's token accounts
+#[derive(Clone, Copy, PartialEq)]
+pub struct TokenBalance {
+    pub amount: u64,
+    pub decimals: u8,
+    pub program_id: &'static str,
+}
+
+impl TokenBalance {
+    fn ui(&self, amount: u64) -> f64 {
+        amount as f64 / 10f64.powi(self.decimals as i32)
+    }
+}
+
+/// Non-zero SPL Token and Token-2022 balances keyed by mint
+pub async fn token_balances(
+    client: &reqwest::Client,
+    wallet: &str,
+) -> Result<BTreeMap<String, TokenBalance>, Box<dyn std::error::Error>> {
+    let mut balances: BTreeMap<String, TokenBalance> = BTreeMap::new();
+    for account in crate::get_parsed_token_accounts(client, wallet).await? {
+        if account.raw_amount() == 0 { continue; }
+        let entry = balances.entry(account.mint().to_string()).or_insert(TokenBalance {
+            amount: 0,
+            decimals: account.decimals(),
+            program_id: account.program_id,
+        });
+        entry.amount = entry.amount.saturating_add(account.raw_amount());
+    }
+    Ok(balances)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TokenChange {
+    /// Mint the wallet did not hold on the previous poll
+    Received,
+    /// Balance went to zero
+    SentAll,
+    Increased,
+    Decreased,
+}
+
+impl TokenChange {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TokenChange::Received => "received",
+            TokenChange::SentAll => "sent_all",
+            TokenChange::Increased => "increased",
+            TokenChange::Decreased => "decreased",
+        }
+    }
+}
+
+pub struct TokenEvent {
+    pub mint: String,
+    pub kind: TokenChange,
+    pub before: u64,
+    pub after: u64,
+    pub balance: TokenBalance,
+}
+
+impl TokenEvent {
+    pub fn delta(&self) -> f64 {
+        self.balance.ui(self.after) - self.balance.ui(self.before)
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "mint": self.mint,
+            "program": if self.balance.program_id == crate::TOKEN_2022_PROGRAM_ID { "token-2022" } else { "spl-token" },
+            "event": self.kind.as_str(),
+            "before": self.balance.ui(self.before),
+            "after": self.balance.ui(self.after),
+            "change": self.delta(),
+            "raw_before": self.before.to_string(),
+            "raw_after": self.after.to_string(),
+            "decimals": self.balance.decimals,
+        })
+    }
+
+    fn print(&self, prefix: &str) {
+        let mint = crate::short(&self.mint);
+        let delta = self.delta();
+        let sign = if delta > 0.0 { "+" } else { "" };
+        match self.kind {
+            TokenChange::Received => println!("{} 🪙 New token {}: {}{}", prefix, mint, sign, delta),
+            TokenChange::SentAll => println!("{} 📤 Sent all {}: {}{}", prefix, mint, sign, delta),
+            TokenChange::Increased | TokenChange::Decreased => println!("{} {} {}: {} ({}{})",
+                prefix, if delta > 0.0 { "📈" } else { "📉" }, mint, self.balance.ui(self.after), sign, delta),
+        }
+    }
+}
+
+/// Per-mint changes between two polls
+pub fn token_events(
+    before: &BTreeMap<String, TokenBalance>,
+    after: &BTreeMap<String, TokenBalance>,
+) -> Vec<TokenEvent> {
+    let mut events = Vec::new();
+    for (mint, new) in after {
+        let old = before.get(mint).map(|b| b.amount).unwrap_or(0);
+        let kind = if old == 0 {
+            TokenChange::Received
+        } else if new.amount > old {
+            TokenChange::Increased
+        } else if new.amount < old {
+            TokenChange::Decreased
+        } else {
+            continue;
+        };
+        events.push(TokenEvent { mint: mint.clone(), kind, before: old, after: new.amount, balance: *new });
+    }
+    for (mint, old) in before {
+        if !after.contains_key(mint) {
+            events.push(TokenEvent { mint: mint.clone(), kind: TokenChange::SentAll, before: old.amount, after: 0, balance: *old });
+        }
+    }
+    events
+}
+
+/// Last transaction reported for a wallet, persisted so a restart resumes without gaps
+pub struct Cursor {
+    /// None when the wallet had no transactions yet: everything that arrives is news
+    pub signature: Option<String>,
+    pub slot: u64,
+}
+
+fn cursor_path(wallet: &str) -> PathBuf {
+    crate::data_dir().join("watch").join(format!("{}.json", wallet))
+}
+
+pub fn load_cursor(wallet: &str) -> Option<Cursor> {
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(cursor_path(wallet)).ok()?).ok()?;
+    let signature = match &json["signature"] {
+        serde_json::Value::Null if json.get("signature").is_some() => None,
+        signature => Some(signature.as_str()?.to_string()),
+    };
+    Some(Cursor { signature, slot: json["slot"].as_u64().unwrap_or(0) })
+}
+
+pub fn save_cursor(wallet: &str, cursor: &Cursor) -> Result<(), Box<dyn std::error::Error>> {
+    let path = cursor_path(wallet);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::json!({ "signature": cursor.signature, "slot": cursor.slot });
+    std::fs::write(path, json.to_string())?;
+    Ok(())
+}
+
+/// Every signature after `cursor`, oldest first: paged all the way back to it, so a long
+/// downtime skips nothing. Without a cursor only the newest is returned.
+async fn new_signatures(
+    client: &reqwest::Client,
+    wallet: &str,
+    cursor: Option<&Cursor>,
+) -> Result<Vec<crate::SignatureInfo>, Box<dyn std::error::Error>> {
+    let mut sigs = match cursor {
+        Some(c) => crate::get_signature_history(client, wallet, usize::MAX, c.signature.as_deref()).await?,
+        None => crate::get_recent_signatures(client, wallet, 1).await?,
+    };
+    // RPC returns newest first; within a slot that order is the reverse of execution
+    sigs.reverse();
+    sigs.sort_by_key(|s| s.slot);
+    Ok(sigs)
+}
+
+/// Per-wallet filters; an event is reported only if it passes all of them
+#[derive(Clone, Default)]
+pub struct WatchRules {
+    /// Smallest SOL balance change (per poll) worth reporting
+    pub min_sol: f64,
+    /// mint -> smallest token change (in token units) worth reporting; other mints report any change
+    pub token_thresholds: HashMap<String, f64>,
+    /// Only report balances going down and transactions the wallet signed
+    pub outgoing_only: bool,
+}
+
+impl WatchRules {
+    /// Rules from a watchlist entry's `rules` object; missing fields fall back to `defaults`
+    fn from_json(json: &serde_json::Value, defaults: &WatchRules) -> WatchRules {
+        let mut rules = defaults.clone();
+        if let Some(min_sol) = json["min_sol"].as_f64() {
+            rules.min_sol = min_sol;
+        }
+        if let Some(outgoing) = json["outgoing_only"].as_bool() {
+            rules.outgoing_only = outgoing;
+        }
+        if let Some(tokens) = json["tokens"].as_object() {
+            for (mint, min) in tokens {
+                if let Some(min) = min.as_f64() {
+                    rules.token_thresholds.insert(mint.clone(), min);
+                }
+            }
+        }
+        rules
+    }
+
+    fn allows_sol(&self, delta: f64) -> bool {
+        delta.abs() >= self.min_sol && (!self.outgoing_only || delta < 0.0)
+    }
+
+    fn allows_token(&self, event: &TokenEvent) -> bool {
+        let delta = event.delta();
+        let min = self.token_thresholds.get(&event.mint).copied().unwrap_or(0.0);
+        delta.abs() >= min && (!self.outgoing_only || delta < 0.0)
+    }
+}
+
+/// A wallet to watch, with its label and rules
+#[derive(Clone)]
+pub struct WatchTarget {
+    pub entry: PortfolioEntry,
+    pub rules: WatchRules,
+}
+
+/// Load a watchlist: the portfolio file format, plus an optional `rules` object per wallet:
+/// `{"wallets": [{"address": "...", "label": "Ops", "rules": {"min_sol": 1.0, "outgoing_only": true, "tokens": {"<mint>": 500}}}]}`
+pub fn load_watchlist(path: &str, defaults: &WatchRules) -> Result<Vec<WatchTarget>, Box<dyn std::error::Error>> {
+    portfolio::read_wallets(path)?.iter().enumerate()
+        .map(|(i, w)| Ok(WatchTarget {
+            entry: portfolio::parse_entry(path, i, w)?,
+            rules: WatchRules::from_json(&w["rules"], defaults),
+        }))
+        .collect()
+}
+
+/// Whether `wallet` signed the transaction. Unknown (fetch failed) counts as signed,
+/// so a flaky RPC can't hide an outgoing transfer.
+async fn signed_by(client: &reqwest::Client, signature: &str, wallet: &str) -> bool {
+    let Ok(Some(tx)) = crate::get_transaction(client, signature).await else { return true };
+    tx["transaction"]["message"]["accountKeys"].as_array().into_iter().flatten()
+        .any(|k| k["pubkey"].as_str() == Some(wallet) && k["signer"].as_bool() == Some(true))
+}
+
+/// Poll state for one watched wallet
+struct WalletWatch {
+    target: WatchTarget,
+    last_balance: f64,
+    last_tokens: Option<BTreeMap<String, TokenBalance>>,
+    cursor: Option<Cursor>,
+    iteration: u64,
+}
+
+impl WalletWatch {
+    fn new(target: WatchTarget) -> Self {
+        let cursor = load_cursor(&target.entry.address);
+        Self { target, last_balance: -1.0, last_tokens: None, cursor, iteration: 0 }
+    }
+
+    async fn poll(&mut self, client: &reqwest::Client, json_output: bool, router: &AlertRouter) {
+        let wallet = self.target.entry.address.as_str();
+        let label = self.target.entry.label.as_str();
+        let rules = &self.target.rules;
+
+        let balance = crate::get_sol_balance(client, wallet).await.unwrap_or(-1.0);
+        // On failure the cursor stays put and the same range is retried next poll
+        let fetched = new_signatures(client, wallet, self.cursor.as_ref()).await.ok();
+        let fetched_ok = fetched.is_some();
+        let sigs = fetched.unwrap_or_default();
+        // A failed fetch keeps the previous baseline rather than reporting everything as sent
+        let tokens = token_balances(client, wallet).await.ok();
+
+        let diff = balance - self.last_balance;
+        let balance_changed = self.last_balance >= 0.0 && balance >= 0.0
+            && diff.abs() > 0.000000001 && rules.allows_sol(diff);
+        let token_changes: Vec<TokenEvent> = match (&self.last_tokens, &tokens) {
+            (Some(before), Some(after)) => token_events(before, after),
+            _ => Vec::new(),
+        }.into_iter().filter(|e| rules.allows_token(e)).collect();
+        let now = chrono::Local::now().format("%H:%M:%S").to_string();
+        let mut alerts = Vec::new();
+        let alert = |severity: Severity, kind: &str, state: &str, message: String| {
+            Alert::new(severity, wallet, label, kind, state, message)
+        };
+
+        // Without a saved cursor the newest signature is only a starting point, not news
+        let new_txs: &[crate::SignatureInfo] = if self.cursor.is_some() { &sigs } else { &[] };
+        for sig in new_txs {
+            if rules.outgoing_only && !signed_by(client, &sig.signature, wallet).await {
+                continue;
+            }
+            alerts.push(alert(Severity::Info, "tx", &sig.signature, format!("New TX {} (slot {}){}",
+                sig.signature, sig.slot, if sig.err { " failed" } else { "" })));
+            if json_output {
+                let event = serde_json::json!({
+                    "time": now,
+                    "wallet": wallet,
+                    "label": label,
+                    "balance": balance,
+                    "change": None::<f64>,
+                    "new_tx": sig.signature,
+                    "slot": sig.slot,
+                    "block_time": sig.block_time,
+                    "failed": sig.err,
+                    "token_changes": [],
+                });
+                println!("{}", event);
+            } else {
+                println!("[{}] [{}] 🔔 New TX: {}...{} (slot {}){}", now, label,
+                    &sig.signature[..16], &sig.signature[sig.signature.len()-8..],
+                    sig.slot, if sig.err { " ❌ failed" } else { "" });
+            }
+        }
+
+        // Money leaving a watched wallet is worth paging about; arrivals are informational
+        let direction = |delta: f64| if delta < 0.0 { Severity::Medium } else { Severity::Info };
+        if balance_changed {
+            alerts.push(alert(direction(diff), "sol", &format!("{:.9}", balance), format!("SOL {:+.9} → {:.9}", diff, balance)));
+        }
+        for change in &token_changes {
+            let state = format!("{}:{}", change.mint, change.after);
+            alerts.push(alert(direction(change.delta()), "token", &state, format!("{} {} {:+} → {}",
+                change.kind.as_str(), change.mint, change.delta(), change.balance.ui(change.after))));
+        }
+
+        if self.iteration == 0 || balance_changed || !token_changes.is_empty() {
+            if json_output {
+                let event = serde_json::json!({
+                    "time": now,
+                    "wallet": wallet,
+                    "label": label,
+                    "balance": balance,
+                    "change": if balance_changed { Some(diff) } else { None },
+                    "new_tx": None::<String>,
+                    "token_changes": token_changes.iter().map(|e| e.to_json()).collect::<Vec<_>>(),
+                });
+                println!("{}", event);
+            } else {
+                let prefix = format!("[{}] [{}]", now, label);
+                if balance_changed {
+                    let arrow = if diff > 0.0 { "📈" } else { "📉" };
+                    println!("{} {} SOL: {:.9} ({}{:.9})",
+                        prefix, arrow, balance,
+                        if diff > 0.0 { "+" } else { "" }, diff);
+                } else if self.iteration == 0 {
+                    println!("{} ✅ SOL: {:.9} | {} tokens", prefix, balance,
+                        tokens.as_ref().map(|t| t.len()).unwrap_or(0));
+                }
+                for change in &token_changes {
+                    change.print(&prefix);
+                }
+            }
+        }
+
+        router.dispatch(&alerts).await;
+
+        // A wallet with no history yet gets an explicit empty cursor, so its first transactions are news
+        let next = match sigs.last() {
+            Some(newest) => Some(Cursor { signature: Some(newest.signature.clone()), slot: newest.slot }),
+            None if self.cursor.is_none() && fetched_ok => Some(Cursor { signature: None, slot: 0 }),
+            None => None,
+        };
+        if let Some(next) = next {
+            if let Err(e) = save_cursor(wallet, &next) {
+                eprintln!("⚠️  [{}] Could not save watch cursor: {}", label, e);
+            }
+            self.cursor = Some(next);
+        }
+        if balance >= 0.0 { self.last_balance = balance; }
+        if tokens.is_some() { self.last_tokens = tokens; }
+        self.iteration += 1;
+    }
+}
+
+/// Watch every target in one process. Each wallet polls on its own schedule;
+/// at most `concurrency` polls hit the RPC at once.
+pub async fn watch(
+    targets: Vec<WatchTarget>,
+    interval_secs: u64,
+    concurrency: usize,
+    router: AlertRouter,
+    json_output: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if targets.is_empty() {
+        return Err("nothing to watch".into());
+    }
+    if !json_output {
+        if let [target] = targets.as_slice() {
+            let wallet = &target.entry.address;
+            println!("👁️  Watching wallet: {}...{}", &wallet[..8], &wallet[wallet.len()-8..]);
+        } else {
+            println!("👁️  Watching {} wallets", targets.len());
+        }
+        println!("    Polling every {}s — Ctrl+C to stop", interval_secs);
+        for route in &router.routes {
+            println!("    Alerting {} (≥ {})", route.sink.name(), route.min_severity.name());
+        }
+        for target in &targets {
+            match load_cursor(&target.entry.address) {
+                Some(Cursor { signature: Some(signature), slot }) => println!("    [{}] resuming after {}... (slot {})",
+                    target.entry.label, &signature[..16.min(signature.len())], slot),
+                Some(Cursor { signature: None, .. }) => println!("    [{}] no transactions yet; reporting the first ones", target.entry.label),
+                None => {}
+            }
+        }
+        println!();
+    }
+
+    let client = reqwest::Client::new();
+    let router = Arc::new(router);
+    let permits = Arc::new(tokio::sync::Semaphore::new(concurrency.max(1)));
+    let mut tasks = tokio::task::JoinSet::new();
+    for target in targets {
+        let client = client.clone();
+        let permits = permits.clone();
+        let router = router.clone();
+        tasks.spawn(async move {
+            let mut watch = WalletWatch::new(target);
+            loop {
+                {
+                    let _permit = permits.acquire().await;
+                    watch.poll(&client, json_output, &router).await;
+                }
+                tokio::time::sleep(tokio::time::Duration::from_secs(interval_secs)).await;
+            }
+        });
+    }
+
+    // Watchers loop forever; this only returns if one of them panics
+    while let Some(joined) = tasks.join_next().await {
+        joined?;
+    }
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use crate::scanner::PROGRAMS;
+
+/// A program the guardian and `--scan-defi` look at
+#[derive(Clone, Debug)]
+pub struct WatchedProgram {
+    pub id: String,
+    pub name: String,
+    pub category: Option<String>,
+    /// Upgrade authority we expect; "none" means the program should be immutable
+    pub expected_authority: Option<String>,
+    /// Hex SHA-256 of the ELF we expect to be deployed
+    pub expected_hash: Option<String>,
+    /// Check interval override, in seconds
+    pub interval_secs: Option<u64>,
+    /// Days without authority activity before it's reported dormant
+    pub dormant_days: Option<u64>,
+}
+
+impl WatchedProgram {
+    /// Whether `authority` matches the expectation (always true without one)
+    pub fn authority_ok(&self, authority: Option<&str>) -> bool {
+        match self.expected_authority.as_deref() {
+            None => true,
+            Some("none") => authority.is_none(),
+            Some(expected) => authority == Some(expected),
+        }
+    }
+
+    pub fn hash_ok(&self, hash: Option<&str>) -> bool {
+        match &self.expected_hash {
+            None => true,
+            Some(expected) => hash.is_some_and(|h| h.eq_ignore_ascii_case(expected)),
+        }
+    }
+}
+
+/// The built-in DeFi protocol table
+pub fn defaults() -> Vec<WatchedProgram> {
+    PROGRAMS.iter().map(|(id, name)| WatchedProgram {
+        id: id.to_string(),
+        name: name.to_string(),
+        category: Some("defi".to_string()),
+        expected_authority: None,
+        expected_hash: None,
+        interval_secs: None,
+        dormant_days: None,
+    }).collect()
+}
+
+/// Load a watchlist file:
+/// `{"include_defaults": false, "programs": [{"id": "...", "name": "Our router", "category": "dex",
+///   "expected_authority": "...", "expected_hash": "...", "interval": 60, "dormant_days": 30}]}`
+/// With `include_defaults`, the built-in table is watched too (file entries win on duplicate ids).
+pub fn load(path: &str) -> Result<Vec<WatchedProgram>, Box<dyn std::error::Error>> {
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)
+        .map_err(|e| format!("{}: {}", path, e))?;
+    let entries = json["programs"].as_array()
+        .ok_or_else(|| format!("{}: expected a \"programs\" array", path))?;
+
+    let mut programs = Vec::new();
+    for (i, p) in entries.iter().enumerate() {
+        let id = p["id"].as_str()
+            .ok_or_else(|| format!("{}: program #{} has no id", path, i + 1))?;
+        if crate::encoding::pubkey_bytes(id).is_err() {
+            return Err(format!("{}: program #{}: '{}' is not a valid address", path, i + 1, id).into());
+        }
+        let text = |key: &str| p[key].as_str().map(|v| v.to_string());
+        programs.push(WatchedProgram {
+            id: id.to_string(),
+            name: text("name").unwrap_or_else(|| id.to_string()),
+            category: text("category"),
+            expected_authority: text("expected_authority"),
+            expected_hash: text("expected_hash"),
+            interval_secs: p["interval"].as_u64(),
+            dormant_days: p["dormant_days"].as_u64(),
+        });
+    }
+
+    if json["include_defaults"].as_bool() == Some(true) {
+        for default in defaults() {
+            if !programs.iter().any(|p| p.id == default.id) {
+                programs.push(default);
+            }
+        }
+    }
+    Ok(programs)
+}
+
+/// Where the program list comes from, so it can be reloaded
+#[derive(Clone)]
+pub enum Source {
+    Builtin,
+    File(String),
+}
+
+impl Source {
+    /// `--programs <file>`, else `$SOLSCAN_HOME/programs.json` if present, else the built-in table
+    pub fn resolve(path: Option<String>) -> Self {
+        match path {
+            Some(path) => Source::File(path),
+            None => {
+                let default = crate::data_dir().join("programs.json");
+                if default.exists() {
+                    Source::File(default.to_string_lossy().to_string())
+                } else {
+                    Source::Builtin
+                }
+            }
+        }
+    }
+
+    pub fn load(&self) -> Result<Vec<WatchedProgram>, Box<dyn std::error::Error>> {
+        match self {
+            Source::Builtin => Ok(defaults()),
+            Source::File(path) => load(path),
+        }
+    }
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Source::Builtin => write!(f, "built-in protocol list"),
+            Source::File(path) => write!(f, "{}", path),
+        }
+    }
+}
@@ -50,7 +50,7 @@ impl SolWeb {
                 self.token_holders.entry(mint.clone()).or_default().push(wallet.clone());
                 
                 // For each token, find largest holders (top accounts)
-                if !self.token_holders.get(mint).map_or(false, |h| h.len() > 3) {
+                if self.token_holders.get(mint).is_none_or(|h| h.len() <= 3) {
                     if let Ok(holders) = self.get_largest_accounts(mint).await {
                         for holder_wallet in &holders {
                             if !self.visited.contains(holder_wallet) {
@@ -150,7 +150,7 @@ impl SolWeb {
     }
 
     /// Print the web as a graph summary
-    pub fn print_web(&self) {
+    pub fn print_web(&self, domains: &HashMap<String, String>) {
         println!("\n🕸️  SolWeb — Wallet Connection Graph");
         println!("═══════════════════════════════════════════");
         println!("  Wallets discovered: {}", self.wallet_tokens.len());
@@ -164,7 +164,7 @@ impl SolWeb {
             })
             .filter(|(_, count)| *count > 1)
             .collect();
-        connections.sort_by(|a, b| b.1.cmp(&a.1));
+        connections.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
         
         if !connections.is_empty() {
             println!("\n  🔗 Connecting Tokens (held by multiple wallets):");
@@ -178,7 +178,7 @@ impl SolWeb {
         let mut wallet_connections: Vec<(&String, usize)> = self.wallet_tokens.iter()
             .map(|(wallet, tokens)| {
                 let shared = tokens.iter()
-                    .filter(|t| self.token_holders.get(*t).map_or(false, |h| {
+                    .filter(|t| self.token_holders.get(*t).is_some_and(|h| {
                         let unique: HashSet<&String> = h.iter().collect();
                         unique.len() > 1
                     }))
@@ -186,13 +186,14 @@ impl SolWeb {
                 (wallet, shared)
             })
             .collect();
-        wallet_connections.sort_by(|a, b| b.1.cmp(&a.1));
+        wallet_connections.sort_by_key(|(_, shared)| std::cmp::Reverse(*shared));
         
         if !wallet_connections.is_empty() {
             println!("\n  🏠 Most Connected Wallets:");
             for (wallet, shared) in wallet_connections.iter().take(5) {
-                println!("    {}...{} → {} shared tokens",
-                    &wallet[..8], &wallet[wallet.len()-4..], shared);
+                println!("    {}...{} → {} shared tokens{}",
+                    &wallet[..8], &wallet[wallet.len()-4..], shared,
+                    domains.get(*wallet).map(|d| format!(" ({})", d)).unwrap_or_default());
             }
         }
 